@@ -0,0 +1,358 @@
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::board_index::BoardIndex;
+use crate::board_index::BoardLocation;
+use crate::cell::Cell;
+use crate::cell::CellFragment;
+use crate::diff::Diff;
+use crate::diff::DiffBuilder;
+use crate::diff::PatchDiff;
+use crate::diff::PatchFragment;
+use crate::error::SolveError;
+
+// A change to the cell at the given index
+#[derive(Debug, Clone)]
+pub struct BoardFragment {
+    index: BoardIndex,
+    fragment: CellFragment,
+}
+
+impl BoardFragment {
+    pub fn new(index: BoardIndex, fragment: CellFragment) -> BoardFragment {
+        BoardFragment { index, fragment }
+    }
+
+    pub fn index(&self) -> &BoardIndex {
+        &self.index
+    }
+
+    pub fn fragment(&self) -> &CellFragment {
+        &self.fragment
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Board {
+    pub cells: [Cell; 81],
+}
+
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.render())
+    }
+}
+
+fn cells_to_values(cells: Vec<&Cell>) -> Vec<u8> {
+    cells.into_iter()
+        .filter_map(|v| v.value)
+        .collect()
+}
+
+impl Board {
+    pub fn new(cells: [Cell; 81]) -> Board {
+        Board { cells }
+    }
+
+    pub fn render(&self) -> String {
+        format!(
+            "┏━━━━━━━┳━━━━━━━┳━━━━━━━┓\n{}\n{}\n{}\n┣━━━━━━━╋━━━━━━━╋━━━━━━━┫\n{}\n{}\n{}\n┣━━━━━━━╋━━━━━━━╋━━━━━━━┫\n{}\n{}\n{}\n┗━━━━━━━┻━━━━━━━┻━━━━━━━┛",
+            self.render_row(0),
+            self.render_row(1),
+            self.render_row(2),
+            self.render_row(3),
+            self.render_row(4),
+            self.render_row(5),
+            self.render_row(6),
+            self.render_row(7),
+            self.render_row(8),
+        )
+    }
+
+    fn render_row(&self, row_num: usize) -> String {
+        format!(
+            "┃ {} {} {} ┃ {} {} {} ┃ {} {} {} ┃",
+            self.cells[row_num * 9],
+            self.cells[(row_num * 9) + 1],
+            self.cells[(row_num * 9) + 2],
+            self.cells[(row_num * 9) + 3],
+            self.cells[(row_num * 9) + 4],
+            self.cells[(row_num * 9) + 5],
+            self.cells[(row_num * 9) + 6],
+            self.cells[(row_num * 9) + 7],
+            self.cells[(row_num * 9) + 8],
+        )
+    }
+
+    fn get_cells(&self, indexes: &[BoardIndex]) -> Vec<&Cell> {
+        indexes
+            .iter()
+            .map(|bi| &self.cells[bi.value as usize])
+            .collect()
+    }
+
+    pub fn get_cell(&self, index: &BoardIndex) -> &Cell {
+        &self.cells[index.value as usize]
+    }
+
+    pub fn get_cell_mut(&mut self, index: &BoardIndex) -> &mut Cell {
+        &mut self.cells[index.value as usize]
+    }
+
+    pub fn diff_builder() -> DiffBuilder<BoardFragment> {
+        Diff::builder()
+    }
+
+    // The options of an unsolved cell, or nothing if the cell is solved
+    pub fn candidates(&self, index: &BoardIndex) -> &[u8] {
+        let cell = self.get_cell(index);
+        if cell.value.is_some() {
+            return &[];
+        }
+        &cell.options
+    }
+
+    pub fn has_candidate(&self, index: &BoardIndex, value: u8) -> bool {
+        self.candidates(index).contains(&value)
+    }
+
+    // A diff that sets the cell's value, clears its other options and removes
+    // the value from its peers' options. The board isn't changed.
+    pub fn placement_diff(&self, index: &BoardIndex, value: u8) -> Diff<BoardFragment> {
+        let others: Vec<u8> = self
+            .get_cell(index)
+            .options
+            .iter()
+            .filter(|o| **o != value)
+            .cloned()
+            .collect();
+        let mut builder = Board::diff_builder();
+        builder.add_fragment(|| BoardFragment::new(
+            index.clone(),
+            CellFragment::builder()
+                .changed_value(None, Some(value))
+                .removed_options(others.clone())
+                .finalize()
+        ));
+
+        for ebi in index.entangled_indexes() {
+            if !self.has_candidate(&ebi, value) {
+                continue;
+            }
+            builder.add_fragment(|| BoardFragment::new(
+                ebi.clone(),
+                CellFragment::builder()
+                    .removed_options(vec![value])
+                    .finalize()
+            ));
+        }
+
+        builder.finalize()
+    }
+
+    // A diff that removes each (index, value) candidate, skipping candidates
+    // the cell doesn't have. Removals from the same cell share a fragment.
+    // The board isn't changed.
+    pub fn elimination_diff(&self, eliminations: &[(BoardIndex, u8)]) -> Diff<BoardFragment> {
+        let mut removed: Vec<(BoardIndex, Vec<u8>)> = vec![];
+        for (index, value) in eliminations {
+            if !self.has_candidate(index, *value) {
+                continue;
+            }
+            match removed.iter_mut().find(|(i, _)| i == index) {
+                Some((_, values)) => {
+                    if !values.contains(value) {
+                        values.push(*value);
+                    }
+                }
+                None => removed.push((index.clone(), vec![*value])),
+            }
+        }
+
+        let fragments = removed
+            .into_iter()
+            .map(|(index, mut values)| {
+                values.sort();
+                BoardFragment::new(
+                    index,
+                    CellFragment::builder()
+                        .removed_options(values)
+                        .finalize()
+                )
+            })
+            .collect();
+        Diff::new(fragments)
+    }
+
+    // Removes the values of solved peers from every unsolved cell's options
+    // without fixing any cells, so the options are accurate candidates
+    pub fn prune_options(&mut self) -> Result<Diff<BoardFragment>, SolveError> {
+        let mut eliminations: Vec<(BoardIndex, u8)> = vec![];
+        for index in self.unsolved_indexes() {
+            let placed = cells_to_values(self.get_cells(&index.entangled_indexes()));
+            let options = self.candidates(&index);
+            if options.iter().all(|o| placed.contains(o)) {
+                let mut conflicting: Vec<u8> = options.to_vec();
+                conflicting.sort();
+                return Err(SolveError::NoCandidates {
+                    index: index.clone(),
+                    row: index.board_row(),
+                    col: index.board_col(),
+                    board_box: index.board_box(),
+                    conflicting,
+                });
+            }
+            for option in options.iter().filter(|o| placed.contains(o)) {
+                eliminations.push((index.clone(), *option));
+            }
+        }
+
+        let diff = self.elimination_diff(&eliminations);
+        self.apply_diff(&diff);
+        Ok(diff)
+    }
+
+    // Removes the values of solved peers from the cell's options, setting
+    // the cell's value if only one option remains. The changes are applied
+    // and returned as a single diff so they can be reverted together.
+    pub fn collapse_cell(&mut self, index: &BoardIndex) -> Result<Diff<BoardFragment>, SolveError> {
+        let mut builder = Board::diff_builder();
+        let cell = self.get_cell(index);
+        if cell.value.is_some() {
+            return Ok(builder.finalize());
+        }
+
+        let entangled_indexes = index.entangled_indexes();
+
+        let entangled_cells = self.get_cells(&entangled_indexes);
+
+        let mut existing_values = cells_to_values(entangled_cells);
+
+        existing_values.sort();
+        existing_values.dedup();
+
+        let s1: HashSet<u8> = cell.options.iter().cloned().collect();
+        let s2: HashSet<u8> = existing_values.iter().cloned().collect();
+        let mut result: Vec<u8> = (&s1 - &s2).into_iter().collect();
+        result.sort();
+
+        if result.is_empty() {
+            let mut conflicting: Vec<u8> = (&s1 & &s2).into_iter().collect();
+            conflicting.sort();
+            return Err(SolveError::NoCandidates {
+                index: index.clone(),
+                row: index.board_row(),
+                col: index.board_col(),
+                board_box: index.board_box(),
+                conflicting,
+            });
+        }
+
+        let mut removed: Vec<u8> = (&s1 & &s2).into_iter().collect();
+        removed.sort();
+
+        if result.len() == 1 {
+            let collapsed_value = *result.first().unwrap();
+            builder.add_fragment(|| BoardFragment::new(
+                index.clone(),
+                CellFragment::builder()
+                    .changed_value(None, Some(collapsed_value))
+                    .removed_options(removed.clone())
+                    .finalize()
+            ));
+            for ebi in &entangled_indexes {
+                if !self.get_cell(ebi).options.contains(&collapsed_value) {
+                    continue;
+                }
+                builder.add_fragment(|| BoardFragment::new(
+                    ebi.clone(),
+                    CellFragment::builder()
+                        .removed_options(vec![collapsed_value])
+                        .finalize()
+                ));
+            }
+        } else if !removed.is_empty() {
+            builder.add_fragment(|| BoardFragment::new(
+                index.clone(),
+                CellFragment::builder()
+                    .removed_options(removed.clone())
+                    .finalize()
+            ));
+        }
+
+        let diff = builder.finalize();
+        self.apply_diff(&diff);
+        Ok(diff)
+    }
+
+    pub fn unsolved_indexes(&self) -> Vec<BoardIndex> {
+        let indexes: Vec<BoardIndex> = self
+            .cells
+            .clone()
+            .into_iter()
+            .filter(|c| c.value.is_none())
+            .map(|c| c.index)
+            .collect();
+        indexes
+    }
+}
+
+impl PatchFragment for Board {
+    type Fragment = BoardFragment;
+
+    fn apply_fragment(&mut self, fragment: &BoardFragment) {
+        self.get_cell_mut(&fragment.index).apply_fragment(&fragment.fragment);
+    }
+
+    fn revert_fragment(&mut self, fragment: &BoardFragment) {
+        self.get_cell_mut(&fragment.index).revert_fragment(&fragment.fragment);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collapse_cell_reports_contradiction() {
+        let line = format!(".12345678{}", "9".to_string() + &".".repeat(71));
+        let mut board = Board::from_line(&line).unwrap();
+        let index = BoardIndex::new(0).unwrap();
+        let error = board.collapse_cell(&index).unwrap_err();
+        assert_eq!(error, SolveError::NoCandidates {
+            index,
+            row: 0,
+            col: 0,
+            board_box: 0,
+            conflicting: vec![1, 2, 3, 4, 5, 6, 7, 8, 9],
+        });
+    }
+
+    #[test]
+    fn collapse_cell_diff_can_be_reverted() {
+        // Cell 0 can only be a 9, which also removes 9 from its peers' options
+        let line = format!(".12345678{}", ".".repeat(72));
+        let original = Board::from_line(&line).unwrap();
+        let mut board = original.clone();
+        let index = BoardIndex::new(0).unwrap();
+        let diff = board.collapse_cell(&index).unwrap();
+        assert_eq!(board.get_cell(&index).value, Some(9));
+        assert!(!board.cells[9].options.contains(&9));
+
+        board.revert_diff(&diff);
+        for (a, b) in original.cells.iter().zip(board.cells.iter()) {
+            assert_eq!(a.value, b.value);
+            assert_eq!(a.options, b.options);
+        }
+
+        board.apply_diff(&diff);
+        assert_eq!(board.get_cell(&index).value, Some(9));
+    }
+
+    #[test]
+    fn solve_reports_contradiction_instead_of_panicking() {
+        let line = format!(".12345678{}", "9".to_string() + &".".repeat(71));
+        let mut board = Board::from_line(&line).unwrap();
+        assert!(board.solve().is_err());
+    }
+}
@@ -21,9 +21,77 @@ impl fmt::Display for BoardIndex {
 impl BoardIndex {
     pub fn new(value: u8) -> Option<BoardIndex> {
         if value <= 80 {
-            return Some(BoardIndex {value});
+            Some(BoardIndex {value})
         } else {
-            return None;
+            None
+        }
+    }
+
+    // The one based row and column of the index, like `r3c5`
+    pub fn label(&self) -> String {
+        format!("r{}c{}", self.value / 9 + 1, self.value % 9 + 1)
+    }
+}
+
+// A row, column or box of the board, numbered from 0 to 8
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum House {
+    Row(u8),
+    Col(u8),
+    Box(u8),
+}
+
+impl fmt::Display for House {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            House::Row(n) => write!(f, "row {}", n + 1),
+            House::Col(n) => write!(f, "column {}", n + 1),
+            House::Box(n) => write!(f, "box {}", n + 1),
+        }
+    }
+}
+
+impl House {
+    pub fn rows() -> Vec<House> {
+        (0..9).map(House::Row).collect()
+    }
+
+    pub fn cols() -> Vec<House> {
+        (0..9).map(House::Col).collect()
+    }
+
+    pub fn boxes() -> Vec<House> {
+        (0..9).map(House::Box).collect()
+    }
+
+    // Every house, boxes first, then rows, then columns
+    pub fn all() -> Vec<House> {
+        [House::boxes(), House::rows(), House::cols()].concat()
+    }
+
+    // The nine indexes in the house, from top-left to bottom-right
+    pub fn indexes(&self) -> Vec<BoardIndex> {
+        let first = match self {
+            House::Row(n) => n * 9,
+            House::Col(n) => *n,
+            House::Box(n) => (n / 3 * 3) * 9 + (n % 3 * 3),
+        };
+        let first = BoardIndex::new(first).unwrap();
+        let mut indexes = match self {
+            House::Row(_) => first.entangled_row_indexes(),
+            House::Col(_) => first.entangled_col_indexes(),
+            House::Box(_) => first.entangled_box_indexes(),
+        };
+        indexes.push(first);
+        indexes.sort();
+        indexes
+    }
+
+    pub fn contains(&self, index: &BoardIndex) -> bool {
+        match self {
+            House::Row(n) => index.board_row() == *n,
+            House::Col(n) => index.board_col() == *n,
+            House::Box(n) => index.board_box() == *n,
         }
     }
 }
@@ -54,6 +122,15 @@ pub trait BoardLocation {
         ((row / 3) * 3) + (col / 3)
     }
 
+    // Returns the row, column and box containing the index
+    fn houses(&self) -> [House; 3] {
+        [
+            House::Row(self.board_row()),
+            House::Col(self.board_col()),
+            House::Box(self.board_box()),
+        ]
+    }
+
     fn index_above(&self) -> Option<BoardIndex> {
         let index = self.board_index().value;
         let row = index / 9;
@@ -62,9 +139,9 @@ pub trait BoardLocation {
         }
         let col = index % 9;
         let value = ((row - 1) * 9) + col;
-        return Some(
+        Some(
             BoardIndex::new(value).unwrap()
-        );
+        )
     }
 
     fn index_below(&self) -> Option<BoardIndex> {
@@ -75,9 +152,9 @@ pub trait BoardLocation {
         }
         let col = index % 9;
         let value = ((row + 1) * 9) + col;
-        return Some(
+        Some(
             BoardIndex::new(value).unwrap()
-        );
+        )
     }
 
     fn index_left(&self) -> Option<BoardIndex> {
@@ -88,9 +165,9 @@ pub trait BoardLocation {
         }
         let row = index / 9;
         let value = (row * 9) + (col - 1);
-        return Some(
+        Some(
             BoardIndex::new(value).unwrap()
-        );
+        )
     }
 
     fn index_right(&self) -> Option<BoardIndex> {
@@ -101,9 +178,9 @@ pub trait BoardLocation {
         }
         let row = index / 9;
         let value = (row * 9) + (col + 1);
-        return Some(
+        Some(
             BoardIndex::new(value).unwrap()
-        );
+        )
     }
 
     fn entangled_row_indexes(&self) -> Vec<BoardIndex> {
@@ -130,7 +207,7 @@ pub trait BoardLocation {
         let box_index = self.board_box();
         let col_start = box_index % 3 * 3;
         let row_start = box_index / 3 * 3;
-        let range1_start = (row_start + 0) * 9 + col_start;
+        let range1_start = row_start * 9 + col_start;
         let range2_start = (row_start + 1) * 9 + col_start;
         let range3_start = (row_start + 2) * 9 + col_start;
         let range1 = range1_start..(range1_start + 3);
@@ -160,6 +237,7 @@ pub trait BoardLocation {
 }
 
 #[cfg(test)]
+#[allow(clippy::useless_vec)]
 mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
     use super::*;
@@ -248,6 +326,21 @@ mod tests {
         assert_eq!(col_indexes, expected_col_indexes);
     }
 
+    #[test]
+    fn test_house_indexes() {
+        let expected_box_indexes: Vec<BoardIndex> = [30, 31, 32, 39, 40, 41, 48, 49, 50]
+            .iter()
+            .map(|i| BoardIndex::new(*i).unwrap())
+            .collect();
+        assert_eq!(House::Box(4).indexes(), expected_box_indexes);
+        let expected_col_indexes: Vec<BoardIndex> = [2, 11, 20, 29, 38, 47, 56, 65, 74]
+            .iter()
+            .map(|i| BoardIndex::new(*i).unwrap())
+            .collect();
+        assert_eq!(House::Col(2).indexes(), expected_col_indexes);
+        assert_eq!(House::Row(8).indexes().first().unwrap().value, 72);
+    }
+
     #[test]
     fn test_entangled_box_indexes_at_box_7() {
         let index = BoardIndex::new(57).unwrap();
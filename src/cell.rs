@@ -1,12 +1,12 @@
 use colored::Colorize;
-use std::{fmt, collections::HashSet};
+use std::fmt;
 
 use crate::board_index::BoardIndex;
 use crate::diff::Diff;
 use crate::diff::DiffBuilder;
 use crate::diff::PatchFragment;
-use crate::diff::ScalarDiffFragment;
 
+#[derive(Default)]
 pub struct CellFragmentBuilder {
     value: Option<[Option<u8>; 2]>,
     options: Option<[Vec<u8>; 2]>,
@@ -23,8 +23,8 @@ impl CellFragmentBuilder {
     }
 
     pub fn removed_options(&mut self, removed: Vec<u8>) -> &mut CellFragmentBuilder {
-        if self.options.is_some() {
-            self.options.as_mut().unwrap()[0] = removed;
+        if let Some(options) = self.options.as_mut() {
+            options[0] = removed;
         } else {
             self.options = Some([
                 removed,
@@ -35,8 +35,8 @@ impl CellFragmentBuilder {
     }
 
     pub fn added_options(&mut self, added: Vec<u8>) -> &mut CellFragmentBuilder {
-        if self.options.is_some() {
-            self.options.as_mut().unwrap()[1] = added;
+        if let Some(options) = self.options.as_mut() {
+            options[1] = added;
         } else {
             self.options = Some([
                 vec![],
@@ -52,16 +52,8 @@ impl CellFragmentBuilder {
     }
 }
 
-impl Default for CellFragmentBuilder {
-    fn default() -> Self {
-        CellFragmentBuilder{
-            value: None,
-            options: None,
-        }
-    }
-}
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct CellFragment {
     value: Option<[Option<u8>; 2]>,
     options: Option<[Vec<u8>; 2]>,
@@ -78,6 +70,16 @@ impl CellFragment {
     pub fn builder() -> CellFragmentBuilder {
         CellFragmentBuilder::new()
     }
+
+    // The value the fragment sets, if it sets one
+    pub fn placed(&self) -> Option<u8> {
+        self.value.and_then(|[_, new]| new)
+    }
+
+    // The options the fragment removes
+    pub fn removed(&self) -> &[u8] {
+        self.options.as_ref().map_or(&[], |[removed, _]| removed)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -91,17 +93,16 @@ pub struct Cell {
 impl Cell {
     pub fn new(index: BoardIndex, input: u8, is_initial: bool) -> Cell {
         let value = if input == 0 { None } else { Some(input) };
-        let options: Vec<u8>;
-        if value.is_some() {
-            options = vec![];
+        let options: Vec<u8> = if value.is_some() {
+            vec![]
         } else {
-            options = vec![1, 2, 3, 4, 5, 6, 7, 8, 9];
-        }
+            vec![1, 2, 3, 4, 5, 6, 7, 8, 9]
+        };
         Cell {
-            index: index,
+            index,
             initial: is_initial,
-            value: value,
-            options: options,
+            value,
+            options,
         }
     }
 
@@ -109,16 +110,16 @@ impl Cell {
         Diff::builder()
     }
 
-    // fn entropy(&self) -> usize {
-    //     if self.value.is_some() {
-    //         return 0;
-    //     }
-    //     self.options.len()
-    // }
+    pub fn entropy(&self) -> usize {
+        if self.value.is_some() {
+            return 0;
+        }
+        self.options.len()
+    }
 }
 
 // https://stackoverflow.com/a/63557337
-fn subtract<T: std::cmp::PartialEq>(a: &mut Vec<T>, b: &Vec<T>) {
+fn subtract<T: std::cmp::PartialEq>(a: &mut Vec<T>, b: &[T]) {
     a.retain(|x| !b.contains(x));
 }
 
@@ -127,36 +128,34 @@ impl PatchFragment for Cell {
 
     fn apply_fragment(&mut self, fragment: &CellFragment) {
         // Apply value changes
-        if fragment.value.is_some() {
-            let [_, new] = fragment.value.unwrap();
+        if let Some([_, new]) = fragment.value {
             self.value = new;
         }
 
         // Apply options changes
-        if fragment.options.is_some() {
-            let [removed, added] = fragment.options.as_ref().unwrap();
+        if let Some([removed, added]) = fragment.options.as_ref() {
 
             // Handle removed options
             subtract(&mut self.options, removed);
 
             // Handle added options
-            self.options.extend(added)
+            self.options.extend(added);
+            self.options.sort();
         }
     }
 
     fn revert_fragment(&mut self, fragment: &CellFragment) {
         // Revert fragment value
-        if fragment.value.is_some() {
-            let [old, _] = fragment.value.unwrap();
+        if let Some([old, _]) = fragment.value {
             self.value = old;
         }
 
         // Revert fragment options
-        if fragment.options.is_some() {
-            let [removed, added] = fragment.options.as_ref().unwrap();
+        if let Some([removed, added]) = fragment.options.as_ref() {
 
             // Handle removed options
             self.options.extend(removed);
+            self.options.sort();
 
             // Handle added options
             subtract(&mut self.options, added);
@@ -166,24 +165,24 @@ impl PatchFragment for Cell {
 
 impl fmt::Display for Cell {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if self.value.is_some() {
-            let mut value = format!("{}", self.value.unwrap());
+        if let Some(value) = self.value {
+            let mut value = format!("{}", value);
             if self.initial {
                 value = value.bold().to_string();
             } else {
                 value = value.dimmed().to_string();
             }
-            return write!(f, "{}", value);
+            write!(f, "{}", value)
         } else {
             let value: String = num_to_superscript(self.options.len())
                 .dimmed()
                 .to_string();
-            return write!(f, "{}", value);
+            write!(f, "{}", value)
         }
     }
 }
 
-fn num_to_superscript(number: usize) -> &'static str {
+pub(crate) fn num_to_superscript(number: usize) -> &'static str {
     match number {
         1 => "\u{00B9}",
         2 => "\u{00B2}",
@@ -31,14 +31,16 @@ impl<F, T: PatchFragment<Fragment = F>> PatchDiff for T {
     fn apply_diff(&mut self, diff: &Diff<Self::Fragment>) {
         diff
             .fragments()
-            .into_iter()
+            .iter()
             .for_each(|f| self.apply_fragment(f));
     }
 
     fn revert_diff(&mut self, diff: &Diff<Self::Fragment>) {
+        // Fragments may depend on earlier ones, so undo them newest first
         diff
             .fragments()
-            .into_iter()
+            .iter()
+            .rev()
             .for_each(|f| self.revert_fragment(f));
     }
 }
@@ -72,7 +74,7 @@ impl<F> Default for DiffBuilder<F> {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Diff<F> {
     fragments: Vec<F>
 }
@@ -89,9 +91,19 @@ impl<F> Diff<F> {
     pub fn fragments(&self) -> &Vec<F> {
         &self.fragments
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.fragments.is_empty()
+    }
+
+    // Appends the other diff's fragments so both can be applied and
+    // reverted as one
+    pub fn extend(&mut self, other: Diff<F>) {
+        self.fragments.extend(other.fragments);
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ScalarDiffFragment<T> {
     value: [T; 2]
 }
@@ -110,7 +122,7 @@ impl <T> ScalarDiffFragment<T> {
     }
 }
 
-trait ScalarDiff: Sized + PatchFragment {
+pub trait ScalarDiff: Sized + PatchFragment {
     fn mut_and_diff(&mut self, value: Self) -> Diff<ScalarDiffFragment<Self>>;
 }
 
@@ -125,7 +137,7 @@ impl <T: Copy + Sized + PatchFragment<Fragment = ScalarDiffFragment<T>>> ScalarD
 
 #[cfg(test)]
 mod tests {
-    use crate::diff::{ScalarDiff, PatchDiff};
+    use crate::diff::{Diff, ScalarDiff, ScalarDiffFragment, PatchDiff};
 
     #[test]
     fn creates_diff_for_u8() {
@@ -169,6 +181,21 @@ mod tests {
         assert_eq!(start, "qux corge");
     }
 
+    #[test]
+    fn reverts_fragments_newest_first() {
+        let mut start: u8 = 1;
+
+        let diff = Diff::new(vec![
+            ScalarDiffFragment::new(1u8, 2u8),
+            ScalarDiffFragment::new(2u8, 3u8),
+        ]);
+        start.apply_diff(&diff);
+        assert_eq!(start, 3u8);
+
+        start.revert_diff(&diff);
+        assert_eq!(start, 1u8);
+    }
+
     // #[test]
     // fn creates_diff_for_vec_i32() {
     //     let mut start: Vec<i32> = vec![1, 2, 3];
@@ -0,0 +1,227 @@
+use crate::board::Board;
+use crate::board_index::BoardIndex;
+
+// Each cell needs a value, and each row, column and box needs each digit
+const COLUMNS: usize = 4 * 81;
+
+// The four constraints a digit in a cell satisfies
+fn constraints(cell: usize, digit: usize) -> [usize; 4] {
+    let (row, col) = (cell / 9, cell % 9);
+    let board_box = row / 3 * 3 + col / 3;
+    [
+        cell,
+        81 + row * 9 + digit,
+        2 * 81 + col * 9 + digit,
+        3 * 81 + board_box * 9 + digit,
+    ]
+}
+
+// Knuth's Dancing Links. Every node is linked to its neighbours in a circular
+// list across its row and another down its column, so covering a column and
+// uncovering it again are cheap. Node 0 is the root and nodes 1 to COLUMNS
+// head the columns.
+struct Links {
+    left: Vec<usize>,
+    right: Vec<usize>,
+    up: Vec<usize>,
+    down: Vec<usize>,
+    column: Vec<usize>,
+    // The (cell, digit) choice the node's row stands for
+    choice: Vec<(usize, usize)>,
+    size: Vec<usize>,
+}
+
+impl Links {
+    fn new() -> Links {
+        let headers = COLUMNS + 1;
+        Links {
+            left: (0..headers).map(|n| (n + headers - 1) % headers).collect(),
+            right: (0..headers).map(|n| (n + 1) % headers).collect(),
+            up: (0..headers).collect(),
+            down: (0..headers).collect(),
+            column: (0..headers).collect(),
+            choice: vec![(0, 0); headers],
+            size: vec![0; headers],
+        }
+    }
+
+    fn add_row(&mut self, cell: usize, digit: usize) {
+        let first = self.left.len();
+        for (n, constraint) in constraints(cell, digit).iter().enumerate() {
+            let node = first + n;
+            let header = constraint + 1;
+            self.left.push(if n == 0 { first + 3 } else { node - 1 });
+            self.right.push(if n == 3 { first } else { node + 1 });
+            self.up.push(self.up[header]);
+            self.down.push(header);
+            let above = self.up[header];
+            self.down[above] = node;
+            self.up[header] = node;
+            self.column.push(header);
+            self.choice.push((cell, digit));
+            self.size[header] += 1;
+        }
+    }
+
+    fn cover(&mut self, header: usize) {
+        self.right[self.left[header]] = self.right[header];
+        self.left[self.right[header]] = self.left[header];
+        let mut row = self.down[header];
+        while row != header {
+            let mut node = self.right[row];
+            while node != row {
+                self.down[self.up[node]] = self.down[node];
+                self.up[self.down[node]] = self.up[node];
+                self.size[self.column[node]] -= 1;
+                node = self.right[node];
+            }
+            row = self.down[row];
+        }
+    }
+
+    fn uncover(&mut self, header: usize) {
+        let mut row = self.up[header];
+        while row != header {
+            let mut node = self.left[row];
+            while node != row {
+                self.size[self.column[node]] += 1;
+                self.down[self.up[node]] = node;
+                self.up[self.down[node]] = node;
+                node = self.left[node];
+            }
+            row = self.up[row];
+        }
+        self.right[self.left[header]] = header;
+        self.left[self.right[header]] = header;
+    }
+
+    // Algorithm X, choosing the column with the fewest rows each time
+    fn search(&mut self, chosen: &mut Vec<usize>, cap: usize, found: &mut Vec<Vec<usize>>) {
+        if self.right[0] == 0 {
+            found.push(chosen.clone());
+            return;
+        }
+        let mut header = self.right[0];
+        let mut best = header;
+        while header != 0 {
+            if self.size[header] < self.size[best] {
+                best = header;
+            }
+            header = self.right[header];
+        }
+        if self.size[best] == 0 {
+            return;
+        }
+
+        self.cover(best);
+        let mut row = self.down[best];
+        while row != best && found.len() < cap {
+            chosen.push(row);
+            let mut node = self.right[row];
+            while node != row {
+                self.cover(self.column[node]);
+                node = self.right[node];
+            }
+            self.search(chosen, cap, found);
+            let mut node = self.left[row];
+            while node != row {
+                self.uncover(self.column[node]);
+                node = self.left[node];
+            }
+            chosen.pop();
+            row = self.down[row];
+        }
+        self.uncover(best);
+    }
+}
+
+impl Board {
+    // Solves the board as an exact cover problem with Dancing Links, which is
+    // much faster than the technique solver when only the answer matters.
+    // Solved cells are kept and unsolved cells may only take their options.
+    // Returns up to `cap` solved copies of the board.
+    pub fn exact_cover_solutions(&self, cap: usize) -> Vec<Board> {
+        let mut links = Links::new();
+        for cell in self.cells.iter() {
+            let digits = match cell.value {
+                Some(value) => vec![value],
+                None => cell.options.clone(),
+            };
+            for digit in digits {
+                links.add_row(cell.index.value as usize, digit as usize - 1);
+            }
+        }
+
+        let mut found = vec![];
+        links.search(&mut vec![], cap, &mut found);
+        found
+            .into_iter()
+            .map(|rows| {
+                let mut board = self.clone();
+                for row in rows {
+                    let (cell, digit) = links.choice[row];
+                    let cell = board.get_cell_mut(&BoardIndex::new(cell as u8).unwrap());
+                    if cell.value.is_none() {
+                        cell.value = Some(digit as u8 + 1);
+                        cell.options = vec![];
+                    }
+                }
+                board
+            })
+            .collect()
+    }
+
+    // The first solution Dancing Links finds, or `None` if there is none
+    pub fn solve_exact_cover(&self) -> Option<Board> {
+        self.exact_cover_solutions(1).pop()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board_index::BoardLocation;
+    use crate::techniques::tests::PUZZLES;
+
+    // Known to need guessing: "AI Escargot"
+    const HARD: &str = "1....7.9..3..2...8..96..5....53..9...1..8...26....4...3......1..4......7..7...3..";
+
+    #[test]
+    fn agrees_with_search() {
+        for puzzle in PUZZLES.iter().chain([HARD].iter()) {
+            let board = Board::from_line(puzzle).unwrap();
+            let solved = board.solve_exact_cover().unwrap();
+            let mut searched = board.clone();
+            searched.search().unwrap();
+            for (a, b) in solved.cells.iter().zip(searched.cells.iter()) {
+                assert_eq!(a.value, b.value);
+                assert_eq!(a.initial, b.initial);
+            }
+        }
+    }
+
+    #[test]
+    fn respects_options() {
+        // Any grid is a solution of an empty board, as long as the first
+        // cell keeps to the one option it's left with
+        let mut board = Board::from_line(&".".repeat(81)).unwrap();
+        board.get_cell_mut(&BoardIndex::new(0).unwrap()).options = vec![1];
+        let solved = board.solve_exact_cover().unwrap();
+        assert_eq!(solved.cells[0].value, Some(1));
+        assert!(solved.unsolved_indexes().is_empty());
+        for cell in solved.cells.iter() {
+            for peer in cell.index.entangled_indexes() {
+                assert_ne!(solved.get_cell(&peer).value, cell.value);
+            }
+        }
+    }
+
+    #[test]
+    fn finds_every_solution_up_to_the_cap() {
+        let line = "..8672531275831649631549827..2763185516928374387415962169284753823157496754396218";
+        assert_eq!(Board::from_line(line).unwrap().exact_cover_solutions(10).len(), 2);
+        assert_eq!(Board::from_line(&".".repeat(81)).unwrap().exact_cover_solutions(3).len(), 3);
+        let conflicting = format!("11{}", ".".repeat(79));
+        assert!(Board::from_line(&conflicting).unwrap().solve_exact_cover().is_none());
+    }
+}
@@ -0,0 +1,30 @@
+use std::fmt;
+
+use crate::board_index::BoardIndex;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolveError {
+    // Every remaining option for the cell is already placed somewhere in its
+    // row, column or box. `conflicting` holds those placed values.
+    NoCandidates {
+        index: BoardIndex,
+        row: u8,
+        col: u8,
+        board_box: u8,
+        conflicting: Vec<u8>,
+    },
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolveError::NoCandidates { index, row, col, board_box, conflicting } => write!(
+                f,
+                "cell {} (row {}, col {}, box {}) has no candidates left, {:?} are already placed among its peers",
+                index, row, col, board_box, conflicting
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SolveError {}
@@ -0,0 +1,265 @@
+use std::fmt;
+
+use crate::board::Board;
+use crate::board_index::BoardIndex;
+use crate::board_index::House;
+use crate::diff::PatchDiff;
+use crate::techniques;
+use crate::techniques::intersections;
+use crate::techniques::subsets;
+use crate::techniques::subsets::positions;
+use crate::techniques::Step;
+use crate::techniques::Technique;
+
+// Every builtin technique Sudoku Explainer has, with the lowest difficulty
+// its steps can be rated, in tenths, from the easiest. Sudoku Explainer
+// always takes the easiest step, so the search can stop at the first
+// technique that can't beat the best step found so far. The chains are the
+// same deductions as its forcing X-chains and forcing chains. Techniques it
+// doesn't have are left out rather than given a made-up difficulty.
+const ORDER: &[(&str, u32)] = &[
+    ("Hidden Single", 12),
+    ("Pointing", 17),
+    ("Claiming", 19),
+    ("Hidden Pair", 20),
+    ("Naked Single", 23),
+    ("Hidden Triple", 25),
+    ("Naked Pair", 30),
+    ("X-Wing", 32),
+    ("Naked Triple", 36),
+    ("Swordfish", 38),
+    ("XY-Wing", 42),
+    ("XYZ-Wing", 44),
+    ("Unique Rectangle Type 1", 45),
+    ("Unique Rectangle Type 2", 45),
+    ("Unique Rectangle Type 4", 45),
+    ("Unique Rectangle Type 3", 46),
+    ("Naked Quad", 50),
+    ("Jellyfish", 52),
+    ("Hidden Quad", 54),
+    ("BUG+1", 56),
+    ("X-Chain", 66),
+    ("XY-Chain", 70),
+    ("AIC", 70),
+    ("Nishio", 75),
+    ("Cell Forcing Chain", 80),
+    ("Region Forcing Chain", 80),
+];
+
+// The extra difficulty of a chain with the given number of nodes, as
+// Sudoku Explainer works it out: a tenth for every step past 4, 6, 8, 12,
+// 16, 24, 32 and so on
+fn length_difficulty(nodes: usize) -> u32 {
+    let mut added = 0;
+    let mut ceil = 4;
+    let mut odd = false;
+    while nodes.saturating_sub(2) > ceil {
+        added += 1;
+        ceil = if odd { ceil * 4 / 3 } else { ceil * 3 / 2 };
+        odd = !odd;
+    }
+    added
+}
+
+// Every digit that has a single place left in a house, with that place
+fn hidden_singles(board: &Board) -> Vec<(BoardIndex, u8)> {
+    let mut singles = vec![];
+    for house in House::all() {
+        for digit in 1..=9 {
+            let cells = positions(board, &house, digit);
+            if cells.len() == 1 && !singles.contains(&(cells[0].clone(), digit)) {
+                singles.push((cells[0].clone(), digit));
+            }
+        }
+    }
+    singles
+}
+
+// Whether applying the step leaves a hidden single that wasn't there
+// before, which makes it one of Sudoku Explainer's cheaper direct hints
+fn is_direct(board: &Board, step: &Step) -> bool {
+    let before = hidden_singles(board);
+    let mut after = board.clone();
+    after.apply_diff(&step.diff);
+    hidden_singles(&after).iter().any(|single| !before.contains(single))
+}
+
+// The Sudoku Explainer difficulty of the step, in tenths, where `base` is
+// the listed difficulty of the technique that found it
+fn step_difficulty(board: &Board, step: &Step, base: u32) -> u32 {
+    let direct = |cheap: u32, full: u32| if is_direct(board, step) { cheap } else { full };
+    match step.technique {
+        "Hidden Single" if matches!(step.houses[0], House::Box(_)) => 12,
+        "Hidden Single" => 15,
+        "Naked Single" => 23,
+        "Pointing" => direct(17, 26),
+        "Claiming" => direct(19, 28),
+        "Hidden Pair" => direct(20, 34),
+        "Hidden Triple" => direct(25, 40),
+        // Chains get harder with their length
+        _ if step.nodes > 0 => base + length_difficulty(step.nodes),
+        _ => base,
+    }
+}
+
+// The steps of the technique to rate. Pointing, claiming and hidden pairs
+// and triples are cheaper when they directly give a single, so every one of
+// their steps is tried. Hidden singles are found in boxes first, which are
+// the cheaper ones, and chains are found shortest first. Other techniques are
+// rated by the first step they find, which is an approximation: Sudoku
+// Explainer would rate every step and may find a shorter forcing chain.
+fn candidate_steps(board: &Board, name: &str) -> Vec<Step> {
+    match name {
+        "Pointing" => intersections::pointing_steps(board).collect(),
+        "Claiming" => intersections::claiming_steps(board).collect(),
+        "Hidden Pair" => subsets::hidden_subsets(board, 2).collect(),
+        "Hidden Triple" => subsets::hidden_subsets(board, 3).collect(),
+        _ => {
+            let technique = techniques::builtin(name).expect("rated techniques are builtin");
+            technique.find_step(board).into_iter().collect()
+        }
+    }
+}
+
+// The easiest step any rated technique allows, with its difficulty in
+// tenths. Techniques that assume uniqueness are only tried when asked for.
+pub(crate) fn easiest_step(board: &Board, assume_unique: bool) -> Option<(u32, Step)> {
+    let mut best: Option<(u32, Step)> = None;
+    for (name, base) in ORDER {
+        if best.as_ref().is_some_and(|(difficulty, _)| difficulty <= base) {
+            break;
+        }
+        if !assume_unique && techniques::builtin(name).is_some_and(|t| t.assumes_unique()) {
+            continue;
+        }
+        for step in candidate_steps(board, name) {
+            let difficulty = step_difficulty(board, &step, *base);
+            if best.as_ref().is_none_or(|(b, _)| difficulty < *b) {
+                best = Some((difficulty, step));
+            }
+        }
+    }
+    best
+}
+
+// A rating in the style of Sudoku Explainer, from 1.2 for hidden singles up
+// to 8 and over for forcing chains
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SeRating {
+    // The hardest step of the whole solve
+    pub er: f64,
+    // The hardest step up to the first placement
+    pub ep: f64,
+    // The hardest step up to the first elimination, which is the first step
+    pub ed: f64,
+}
+
+impl fmt::Display for SeRating {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ER {:.1}/EP {:.1}/ED {:.1}", self.er, self.ep, self.ed)
+    }
+}
+
+impl Board {
+    // Rates the puzzle the way Sudoku Explainer does, by always taking the
+    // easiest step left. Like Sudoku Explainer this assumes the puzzle has a
+    // single solution. Puzzles the techniques can't solve get no rating.
+    pub fn se_rating(&self) -> Option<SeRating> {
+        let mut board = self.clone();
+        board.prune_options().ok()?;
+        let mut er = 0;
+        let mut ep: Option<u32> = None;
+        let mut ed: Option<u32> = None;
+        while !board.unsolved_indexes().is_empty() {
+            let (difficulty, step) = easiest_step(&board, true)?;
+            er = er.max(difficulty);
+            if ed.is_none() {
+                ed = Some(er);
+            }
+            if ep.is_none() && !step.placements().is_empty() {
+                ep = Some(er);
+            }
+            board.apply_diff(&step.diff);
+        }
+        let tenths = |t: u32| t as f64 / 10.0;
+        Some(SeRating { er: tenths(er), ep: tenths(ep.unwrap_or(er)), ed: tenths(ed.unwrap_or(er)) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Builtin techniques Sudoku Explainer doesn't have, which the rating
+    // leaves out rather than guess a difficulty for. Their deductions are
+    // found by the chains and forcing chains instead, as Sudoku Explainer
+    // would find them.
+    const NOT_IN_SUDOKU_EXPLAINER: &[&str] = &[
+        "Skyscraper",
+        "2-String Kite",
+        "Turbot Fish",
+        "Empty Rectangle",
+        "Color Trap",
+        "Color Wrap",
+        "Finned X-Wing",
+        "Finned Swordfish",
+        "Finned Jellyfish",
+        "W-Wing",
+        "WXYZ-Wing",
+        "Sue de Coq",
+        "ALS-XZ",
+        "ALS-XY-Wing",
+        "Death Blossom",
+        "Template",
+        "Unique Rectangle Type 5",
+        "Unique Rectangle Type 6",
+        "Hidden Unique Rectangle",
+    ];
+
+    #[test]
+    fn follows_sudoku_explainer_chain_lengths() {
+        let added: Vec<u32> = [4, 6, 7, 8, 9, 10, 11, 14, 15, 19].iter().map(|n| length_difficulty(*n)).collect();
+        assert_eq!(added, vec![0, 0, 1, 1, 2, 2, 3, 3, 4, 5]);
+    }
+
+    #[test]
+    fn lists_every_builtin_technique_in_order() {
+        for technique in techniques::TECHNIQUES.iter().chain(techniques::UNIQUENESS_TECHNIQUES) {
+            let rated = ORDER.iter().any(|(name, _)| *name == technique.name());
+            let left_out = NOT_IN_SUDOKU_EXPLAINER.contains(&technique.name());
+            assert!(rated != left_out, "{} should be either rated or left out", technique.name());
+        }
+        assert!(ORDER.windows(2).all(|pair| pair[0].1 <= pair[1].1));
+    }
+
+    #[test]
+    fn rates_like_sudoku_explainer() {
+        // Hidden singles in boxes are enough for this one
+        let easy = Board::from_line(".4.6.2.31.....16.96..54.827..276..8.5.6....74.87..5.6216..8..5.82...7.9.7....62..").unwrap();
+        let rating = easy.se_rating().unwrap();
+        assert_eq!(rating, SeRating { er: 1.2, ep: 1.2, ed: 1.2 });
+        assert_eq!(rating.to_string(), "ER 1.2/EP 1.2/ED 1.2");
+
+        // The hardest steps of these have the difficulties Sudoku Explainer
+        // publishes for its techniques: 2.6 for a pointing pair that doesn't
+        // directly give a single, 3.2 for an X-Wing, 3.8 for a Swordfish and
+        // 4.2 for an XY-Wing
+        let rated = [
+            ("003020600900305001001806400008102900700000008006708200002609500800203009005010300", 1.2, 1.2, 1.2),
+            ("100000569492056108056109240009640801064010000218035604040500016905061402621000005", 3.2, 3.2, 2.6),
+            ("..9748...7.........2.1.9.....7...24..64.1.59..98...3.....8.3.2.........6...2759..", 3.8, 1.2, 1.2),
+            ("900040000000600031020000090000700020002935600070002000060000073510009000000080009", 4.2, 1.2, 1.2),
+        ];
+        for (puzzle, er, ep, ed) in rated {
+            let rating = Board::from_line(puzzle).unwrap().se_rating().unwrap();
+            assert_eq!(rating, SeRating { er, ep, ed }, "{}", puzzle);
+        }
+    }
+
+    #[test]
+    fn gives_no_rating_when_the_techniques_get_stuck() {
+        // 1, 4 and 5 could swap around a loop of six cells
+        let ambiguous = Board::from_line("948672531275831649631..9827492763185516928374387..5962169284753823..7496754396218").unwrap();
+        assert!(ambiguous.se_rating().is_none());
+    }
+}
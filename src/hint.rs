@@ -0,0 +1,167 @@
+use crate::board::Board;
+use crate::board::BoardFragment;
+use crate::diff::Diff;
+use crate::explainer;
+use crate::solver::Solver;
+use crate::techniques::explain::cell_list;
+use crate::techniques::explain::list;
+use crate::techniques::explain::sentence_case;
+use crate::techniques::Step;
+
+// How much of a hint to give away, from the least to the most
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HintLevel {
+    // Where to look
+    Region,
+    // Where to look and which technique to use there
+    Technique,
+    // The whole deduction
+    Deduction,
+}
+
+impl HintLevel {
+    pub fn all() -> [HintLevel; 3] {
+        [HintLevel::Region, HintLevel::Technique, HintLevel::Deduction]
+    }
+}
+
+// The next logical step for a board, found without changing it
+#[derive(Debug, Clone)]
+pub struct Hint {
+    pub step: Step,
+    // Turns the board the hint was asked for into the board after the step.
+    // Besides the step this removes candidates already ruled out by solved
+    // peers, which the step relies on.
+    pub diff: Diff<BoardFragment>,
+}
+
+impl Hint {
+    pub fn description(&self) -> String {
+        self.step.explanation()
+    }
+
+    // The houses of the pattern, or its cells when it isn't tied to houses
+    fn region(&self) -> String {
+        if self.step.houses.is_empty() {
+            cell_list(&self.step.cells)
+        } else {
+            list(&self.step.houses)
+        }
+    }
+
+    // The technique's name as it would appear mid-sentence, like "hidden
+    // single" but "X-Wing"
+    fn technique(&self) -> String {
+        let name = sentence_case(self.step.technique);
+        let mut chars = name.chars();
+        match (chars.next(), chars.filter(|c| c.is_uppercase()).count()) {
+            (Some(first), 0) if first.is_uppercase() => format!("{}{}", first.to_lowercase(), &name[first.len_utf8()..]),
+            _ => name,
+        }
+    }
+
+    // The hint up to the given level, like "Look at box 2", then "Try the
+    // hidden single technique in box 2", then the full explanation
+    pub fn reveal(&self, level: HintLevel) -> String {
+        match level {
+            HintLevel::Region => format!("Look at {}", self.region()),
+            HintLevel::Technique => format!("Try the {} technique in {}", self.technique(), self.region()),
+            HintLevel::Deduction => self.description(),
+        }
+    }
+}
+
+impl Board {
+    // The easiest step the default techniques find by Sudoku Explainer's
+    // difficulties, without applying it. Techniques it doesn't rate are only
+    // used when none of the others finds a step.
+    pub fn hint(&self) -> Option<Hint> {
+        self.hint_from(|board| {
+            explainer::easiest_step(board, false)
+                .map(|(_, step)| step)
+                .or_else(|| Solver::new().next_step(board))
+        })
+    }
+
+    // The first step the solver's techniques find, without applying it
+    pub fn hint_with(&self, solver: &Solver) -> Option<Hint> {
+        self.hint_from(|board| solver.next_step(board))
+    }
+
+    // The step `find` picks once the options are pruned. No hint is given
+    // for a board with a cell that has no candidates left.
+    fn hint_from<F>(&self, find: F) -> Option<Hint>
+    where
+        F: Fn(&Board) -> Option<Step>,
+    {
+        let mut board = self.clone();
+        let mut diff = board.prune_options().ok()?;
+        let step = find(&board)?;
+        diff.extend(step.diff.clone());
+        Some(Hint { step, diff })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board_index::BoardIndex;
+    use crate::board_index::House;
+    use crate::diff::PatchDiff;
+
+    const PUZZLE: &str = "200080300060070084030500209000105408000000000402706000301007040720040060004010003";
+
+    #[test]
+    fn hints_without_changing_the_board() {
+        let board = Board::from_line(PUZZLE).unwrap();
+        let hint = board.hint().unwrap();
+        let mut applied = board.clone();
+        applied.apply_diff(&hint.diff);
+        let (index, value) = hint.step.placements()[0].clone();
+        assert_eq!(applied.get_cell(&index).value, Some(value));
+        applied.revert_diff(&hint.diff);
+        assert_eq!(applied.get_cell(&index).options, board.get_cell(&index).options);
+    }
+
+    #[test]
+    fn hints_the_easiest_step_first() {
+        // The default solver would start with a naked single, but a hidden
+        // single in a box is easier to spot
+        let board = Board::from_line(PUZZLE).unwrap();
+        let mut pruned = board.clone();
+        pruned.prune_options().unwrap();
+        assert_eq!(Solver::new().next_step(&pruned).unwrap().technique, "Naked Single");
+        let hint = board.hint().unwrap();
+        assert_eq!(hint.step.technique, "Hidden Single");
+        assert!(matches!(hint.step.houses[0], House::Box(_)));
+    }
+
+    #[test]
+    fn reveals_hints_progressively() {
+        let mut board = Board::from_line(PUZZLE).unwrap();
+        board.prune_options().unwrap();
+        let solver = Solver::from_names(&["Hidden Single"]).unwrap();
+        let hint = board.hint_with(&solver).unwrap();
+        let reveals: Vec<String> = HintLevel::all().iter().map(|l| hint.reveal(*l)).collect();
+        assert_eq!(reveals, vec![
+            "Look at box 1",
+            "Try the hidden single technique in box 1",
+            "Hidden single: 4 can only go in r1c2 within box 1",
+        ]);
+    }
+
+    #[test]
+    fn lowercases_non_ascii_technique_names() {
+        let board = Board::from_line(&".".repeat(81)).unwrap();
+        let index = BoardIndex::new(0).unwrap();
+        let step = Step::new("Élimination", vec![index.clone()], vec![5], vec![], board.elimination_diff(&[(index, 5)]));
+        let hint = Hint { step, diff: Diff::new(vec![]) };
+        assert_eq!(hint.reveal(HintLevel::Technique), "Try the élimination technique in r1c1");
+    }
+
+    #[test]
+    fn gives_no_hint_for_a_stuck_board() {
+        let board = Board::from_line(&".".repeat(81)).unwrap();
+        assert!(board.hint_with(&Solver::from_names(&["Naked Single"]).unwrap()).is_none());
+    }
+}
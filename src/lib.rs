@@ -0,0 +1,14 @@
+pub mod board;
+pub mod board_index;
+pub mod cell;
+pub mod diff;
+pub mod dlx;
+pub mod error;
+pub mod explainer;
+pub mod hint;
+pub mod parse;
+pub mod propagate;
+pub mod rating;
+pub mod search;
+pub mod solver;
+pub mod techniques;
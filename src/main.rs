@@ -1,264 +1,145 @@
-use std::collections::HashSet;
-use std::fmt;
+use std::env;
+use std::io::{self, Read};
+use std::process;
 
-mod board_index;
-mod cell;
-mod diff;
+use sudoku_solver::board::Board;
+use sudoku_solver::hint::HintLevel;
+use sudoku_solver::solver::Solver;
 
-use board_index::BoardLocation;
-use board_index::BoardIndex;
-use cell::Cell;
+// https://sudoku.com/easy/
+const DEFAULT_PUZZLE: &str = ".4.6.2.31.....16.96..54.827..276..8.5.6....74.87..5.6216..8..5.82...7.9.7....62..";
 
-use crate::cell::CellFragment;
-use crate::diff::Diff;
-use crate::diff::PatchDiff;
+const FLAGS: &[&str] = &["--assume-unique", "--log", "--hint", "--rate", "--dlx", "--count"];
+// Flags that are given a value, like `--count=5`
+const VALUE_FLAGS: &[&str] = &["--count=", "--techniques="];
 
-#[derive(Debug, Clone)]
-struct Board {
-    cells: [Cell; 81],
-}
-
-impl fmt::Display for Board {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.render())
-    }
-}
-
-fn cells_to_values(cells: Vec<&Cell>) -> Vec<u8> {
-    cells.into_iter()
-        .filter(|v| v.value.is_some())
-        .map(|v| v.value.clone().unwrap())
-        .collect()
-}
-
-impl Board {
-    fn new(cells: [Cell; 81]) -> Board {
-        Board { cells: cells }
-    }
-
-    fn render(&self) -> String {
-        format!(
-            "┏━━━━━━━┳━━━━━━━┳━━━━━━━┓\n{}\n{}\n{}\n┣━━━━━━━╋━━━━━━━╋━━━━━━━┫\n{}\n{}\n{}\n┣━━━━━━━╋━━━━━━━╋━━━━━━━┫\n{}\n{}\n{}\n┗━━━━━━━┻━━━━━━━┻━━━━━━━┛",
-            self.render_row(0),
-            self.render_row(1),
-            self.render_row(2),
-            self.render_row(3),
-            self.render_row(4),
-            self.render_row(5),
-            self.render_row(6),
-            self.render_row(7),
-            self.render_row(8),
-        )
-    }
-
-    fn render_row(&self, row_num: usize) -> String {
-        format!(
-            "┃ {} {} {} ┃ {} {} {} ┃ {} {} {} ┃",
-            self.cells[(row_num * 9) + 0],
-            self.cells[(row_num * 9) + 1],
-            self.cells[(row_num * 9) + 2],
-            self.cells[(row_num * 9) + 3],
-            self.cells[(row_num * 9) + 4],
-            self.cells[(row_num * 9) + 5],
-            self.cells[(row_num * 9) + 6],
-            self.cells[(row_num * 9) + 7],
-            self.cells[(row_num * 9) + 8],
-        )
-    }
-
-    fn get_cells(&self, indexes: &Vec<BoardIndex>) -> Vec<&Cell> {
-        indexes
-            .iter()
-            .map(|bi| &self.cells[bi.value as usize])
-            .collect()
+fn main() {
+    let (flags, args): (Vec<String>, Vec<String>) = env::args().skip(1).partition(|a| a.starts_with("--"));
+    let unknown = flags
+        .iter()
+        .find(|f| !FLAGS.contains(&f.as_str()) && !VALUE_FLAGS.iter().any(|v| f.starts_with(v)));
+    if let Some(flag) = unknown {
+        eprintln!("Unknown flag {}", flag);
+        process::exit(1);
     }
-
-    pub fn get_cell(&self, index: &BoardIndex) -> &Cell {
-        &self.cells[index.value as usize]
+    // `--assume-unique` allows deductions that are only valid for puzzles
+    // with a single solution
+    let assume_unique = flags.iter().any(|f| f == "--assume-unique");
+    // `--log` prints every step taken with an explanation
+    let log = flags.iter().any(|f| f == "--log");
+    // `--hint` prints the next step bit by bit instead of solving
+    let hint = flags.iter().any(|f| f == "--hint");
+    // `--rate` grades the puzzle instead of solving it
+    let rate = flags.iter().any(|f| f == "--rate");
+    // `--dlx` solves with Dancing Links, skipping the techniques
+    let dlx = flags.iter().any(|f| f == "--dlx");
+    // `--count` reports whether the puzzle has one solution, and
+    // `--count=N` counts solutions up to N
+    let count = flags.iter().find_map(|f| match f.as_str() {
+        "--count" => Some("2"),
+        _ => f.strip_prefix("--count="),
+    });
+    let count = count.map(|cap| match cap.parse::<usize>() {
+        Ok(cap) if cap > 0 => cap,
+        _ => {
+            eprintln!("Unable to count solutions: {:?} isn't a positive number", cap);
+            process::exit(1);
+        }
+    });
+    // `--techniques=naked-single,x-wing,...` picks the techniques to solve
+    // with, in order, instead of the defaults
+    let techniques = flags.iter().find_map(|f| f.strip_prefix("--techniques="));
+    let mut solver = match techniques {
+        Some(names) => match Solver::from_names(&names.split(',').collect::<Vec<&str>>()) {
+            Ok(solver) => solver,
+            Err(e) => {
+                eprintln!("Unable to configure solver: {}", e);
+                process::exit(1);
+            }
+        },
+        None => Solver::new(),
+    };
+    if assume_unique {
+        solver.assume_unique();
     }
+    let puzzle = args.first().cloned().unwrap_or_else(|| DEFAULT_PUZZLE.to_string());
+    // A puzzle of `-` reads a pasted grid from stdin
+    let parsed = if puzzle == "-" {
+        let mut grid = String::new();
+        if let Err(e) = io::stdin().read_to_string(&mut grid) {
+            eprintln!("Unable to read puzzle from stdin: {}", e);
+            process::exit(1);
+        }
+        Board::from_grid(&grid)
+    } else {
+        Board::from_line(&puzzle)
+    };
+    let mut b = match parsed {
+        Ok(board) => board,
+        Err(e) => {
+            eprintln!("Unable to load puzzle: {}", e);
+            process::exit(1);
+        }
+    };
+    println!("Starting 🤖\n{}", b);
 
-    pub fn get_cell_mut(&mut self, index: &BoardIndex) -> &mut Cell {
-        &mut self.cells[index.value as usize]
+    if let Some(cap) = count {
+        println!("Solutions: {}", b.count_solutions(cap));
+        return;
     }
 
-    pub fn collapse_cell(&mut self, index: &BoardIndex) {
-        let cell = self.get_cell(index);
-        if cell.value.is_some() {
-            return;
+    if rate {
+        println!("Rated {}", b.rate());
+        match b.se_rating() {
+            Some(rating) => println!("Sudoku Explainer rating {}", rating),
+            None => println!("No Sudoku Explainer rating, the techniques get stuck"),
         }
+        return;
+    }
 
-        let entangled_indexes = index.entangled_indexes();
-
-        let entangled_cells = self.get_cells(&entangled_indexes);
-
-        let mut existing_values = cells_to_values(entangled_cells);
-
-        existing_values.sort();
-        existing_values.dedup();
-
-        let s1: HashSet<u8> = cell.options.iter().cloned().collect();
-        let s2: HashSet<u8> = existing_values.iter().cloned().collect();
-        let result: Vec<u8> = (&s1 - &s2).into_iter().collect();
-
-        if result.len() == 0 {
-            println!("Comparing s1 to s2: {:?}, {:?}", s1, s2);
-            panic!("Diff between available options and possible options was 0");
+    if dlx {
+        match b.solve_exact_cover() {
+            Some(solved) => println!("Solved! 💪\n{}", solved),
+            None => println!("No solution 🤷\n{}", b),
         }
+        return;
+    }
 
-        if result.len() == 1 {
-            let collapsed_value = *result.first().unwrap();
-            self.get_cell_mut(index).value = Some(collapsed_value);
-            for ebi in &entangled_indexes {
-                let cell = self.get_cell_mut(&ebi);
-                cell.options.retain(|i| *i != collapsed_value);
+    if hint {
+        // The default techniques give the easiest step, and configured ones
+        // the first step in their order
+        let hinted = if techniques.is_none() && !assume_unique { b.hint() } else { b.hint_with(&solver) };
+        match hinted {
+            Some(hint) => {
+                for (n, level) in HintLevel::all().iter().enumerate() {
+                    println!("Hint {}: {}", n + 1, hint.reveal(*level));
+                }
             }
-        } else {
-            self.get_cell_mut(index).options = result;
+            None => println!("No hint 🤷"),
         }
+        return;
     }
 
-    pub fn unsolved_indexes(&self) -> Vec<BoardIndex> {
-        let indexes: Vec<BoardIndex> = self
-            .cells
-            .clone()
-            .into_iter()
-            .filter(|c| c.value.is_none())
-            .map(|c| c.index)
-            .collect();
-        indexes
+    let solved = if log {
+        solver.solve_logged(&mut b).map(|log| {
+            print!("{}", log);
+            if !log.solved {
+                println!("Stuck with {} cells left, searching for the rest", b.unsolved_indexes().len());
+            }
+            log.solved
+        })
+    } else {
+        solver.solve(&mut b)
+    };
+    match solved {
+        Ok(true) => println!("Solved! 💪\n{}", b),
+        Ok(false) => match b.search() {
+            Some(stats) => println!("Solved after {} guesses! 🎲\n{}", stats.guesses, b),
+            None => println!("No solution 🤷\n{}", b),
+        },
+        Err(e) => {
+            eprintln!("Invalid puzzle 💥 {}\n{}", e, b);
+            process::exit(1);
+        }
     }
 }
-
-fn c(index: u8, value: u8) -> Cell {
-    let bi = BoardIndex::new(index).unwrap();
-    Cell::new(bi, value, false)
-}
-
-fn i(index: u8, value: u8) -> Cell {
-    let bi = BoardIndex::new(index).unwrap();
-    Cell::new(bi, value, true)
-}
-
-fn main() {
-    // https://sudoku.com/easy/
-    // let cells: [Cell; 81] = [
-    //     c(0, 0),
-    //     i(1, 4),
-    //     c(2, 0),
-    //     i(3, 6),
-    //     c(4, 0),
-    //     i(5, 2),
-    //     c(6, 0),
-    //     i(7, 3),
-    //     i(8, 1),
-    //     c(9, 0),
-    //     c(10, 0),
-    //     c(11, 0),
-    //     c(12, 0),
-    //     c(13, 0),
-    //     i(14, 1),
-    //     i(15, 6),
-    //     c(16, 0),
-    //     i(17, 9),
-    //     i(18, 6),
-    //     c(19, 0),
-    //     c(20, 0),
-    //     i(21, 5),
-    //     i(22, 4),
-    //     c(23, 0),
-    //     i(24, 8),
-    //     i(25, 2),
-    //     i(26, 7),
-    //     c(27, 0),
-    //     c(28, 0),
-    //     i(29, 2),
-    //     i(30, 7),
-    //     i(31, 6),
-    //     c(32, 0),
-    //     c(33, 0),
-    //     i(34, 8),
-    //     c(35, 0),
-    //     i(36, 5),
-    //     c(37, 0),
-    //     i(38, 6),
-    //     c(39, 0),
-    //     c(40, 0),
-    //     c(41, 0),
-    //     c(42, 0),
-    //     i(43, 7),
-    //     i(44, 4),
-    //     c(45, 0),
-    //     i(46, 8),
-    //     i(47, 7),
-    //     c(48, 0),
-    //     c(49, 0),
-    //     i(50, 5),
-    //     c(51, 0),
-    //     i(52, 6),
-    //     i(53, 2),
-    //     i(54, 1),
-    //     i(55, 6),
-    //     c(56, 0),
-    //     c(57, 0),
-    //     i(58, 8),
-    //     c(59, 0),
-    //     c(60, 0),
-    //     i(61, 5),
-    //     c(62, 0),
-    //     i(63, 8),
-    //     i(64, 2),
-    //     c(65, 0),
-    //     c(66, 0),
-    //     c(67, 0),
-    //     i(68, 7),
-    //     c(69, 0),
-    //     i(70, 9),
-    //     c(71, 0),
-    //     i(72, 7),
-    //     c(73, 0),
-    //     c(74, 0),
-    //     c(75, 0),
-    //     c(76, 0),
-    //     i(77, 6),
-    //     i(78, 2),
-    //     c(79, 0),
-    //     c(80, 0),
-    // ];
-
-    // let mut b = Board::new(cells);
-    // println!("Starting 🤖\n{}", b);
-
-    // for i in 1..=10 {
-    //     let num_unsolved = b.unsolved_indexes().len();
-    //     if num_unsolved == 0 {
-    //         println!("Solved! 💪\n{}", b);
-    //         break;
-    //     }
-    //     println!("Starting itteration {} ({} unsolved):\n{}", i, num_unsolved, b);
-
-    //     // Do one attempt at each cell starting from top-left to bottom-right
-    //     for index in b.unsolved_indexes() {
-    //         b.collapse_cell(&index);
-    //     }
-    // }
-    let index = BoardIndex::new(0).unwrap();
-    let mut cell = Cell::new(index, 0, true);
-    println!("Cell is: {:?}", cell);
-
-    let diff = Cell::diff_builder()
-        .add_fragment(||
-            CellFragment::builder()
-                .removed_options(vec![1, 2, 3])
-                .finalize()
-        )
-        .finalize();
-    println!("CellDiff is: {:?}", diff);
-
-
-    cell.apply_diff(&diff);
-    println!("Cell is now: {:?}", cell);
-
-    cell.revert_diff(&diff);
-    println!("Cell is now: {:?}", cell);
-
-}
@@ -0,0 +1,246 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::board::Board;
+use crate::board_index::BoardIndex;
+use crate::cell::num_to_superscript;
+use crate::cell::Cell;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    // A character that isn't a digit, `0` or `.` was found at the given
+    // (zero based) character position
+    InvalidCharacter { character: char, position: usize },
+    // A character that can't be read as a cell or as grid decoration was
+    // found at the given (one based) line and column
+    InvalidGridCharacter { character: char, line: usize, column: usize },
+    // The input didn't contain exactly 81 cells
+    InvalidLength { length: usize },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::InvalidCharacter { character, position } => write!(
+                f,
+                "invalid character {:?} at position {}",
+                character, position
+            ),
+            ParseError::InvalidGridCharacter { character, line, column } => write!(
+                f,
+                "invalid character {:?} at line {} column {}",
+                character, line, column
+            ),
+            ParseError::InvalidLength { length } => write!(
+                f,
+                "expected 81 cells but found {}",
+                length
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+fn char_to_value(character: char) -> Option<u8> {
+    match character {
+        '0' | '.' => Some(0),
+        '1'..='9' => Some(character as u8 - b'0'),
+        _ => None,
+    }
+}
+
+// Maps a glyph from a pasted grid to a cell value, where `None` means the
+// glyph is decoration that should be skipped
+fn grid_char_to_value(character: char) -> Result<Option<u8>, ()> {
+    if let Some(value) = char_to_value(character) {
+        return Ok(Some(value));
+    }
+    match character {
+        // Other common blank markers
+        '_' | '*' | 'x' | 'X' => Ok(Some(0)),
+        // ASCII separators
+        '|' | '-' | '+' | '=' | ':' | '!' => Ok(None),
+        // Box drawing characters, like the ones used by `Board::render`
+        '\u{2500}'..='\u{257F}' => Ok(None),
+        c if c.is_whitespace() => Ok(None),
+        // Candidate counts rendered for unsolved cells
+        c if (1..=9).any(|n| num_to_superscript(n).starts_with(c)) => Ok(Some(0)),
+        _ => Err(()),
+    }
+}
+
+// The characters of the line with ANSI escape sequences (like the ones added
+// by `colored`) skipped, so the solver's own output can be read back even when
+// it wasn't printed to a tty. Each character keeps its (zero based) column in
+// the original line.
+fn visible_chars(line: &str) -> Vec<(usize, char)> {
+    let mut visible = Vec::with_capacity(line.len());
+    let mut chars = line.chars().enumerate();
+    while let Some((column, c)) = chars.next() {
+        if c == '\u{1b}' {
+            // Skip up to and including the final byte of the sequence
+            for (_, c) in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            visible.push((column, c));
+        }
+    }
+    visible
+}
+
+fn values_to_board(values: &[u8]) -> Board {
+    let cells: [Cell; 81] = std::array::from_fn(|i| {
+        let index = BoardIndex::new(i as u8).unwrap();
+        let value = values[i];
+        Cell::new(index, value, value != 0)
+    });
+    Board::new(cells)
+}
+
+impl Board {
+    // Parses the single line format where each of the 81 cells is given in
+    // order from top-left to bottom-right, using `0` or `.` for blank cells.
+    // Surrounding whitespace is ignored, though positions in errors still
+    // count it.
+    pub fn from_line(line: &str) -> Result<Board, ParseError> {
+        let skipped = line.chars().count() - line.trim_start().chars().count();
+        let mut values: Vec<u8> = Vec::with_capacity(81);
+        for (position, character) in line.trim().chars().enumerate() {
+            match char_to_value(character) {
+                Some(value) => values.push(value),
+                None => return Err(ParseError::InvalidCharacter { character, position: position + skipped }),
+            }
+        }
+
+        if values.len() != 81 {
+            return Err(ParseError::InvalidLength { length: values.len() });
+        }
+
+        Ok(values_to_board(&values))
+    }
+
+    // Parses a multi-line grid, ignoring ASCII (`|`, `-`, `+`) and box
+    // drawing decoration. Blank cells may be given as `0`, `.` or `_`, and the
+    // superscript candidate counts printed by `Board::render` are read as
+    // blanks, so rendered boards can be parsed back.
+    pub fn from_grid(grid: &str) -> Result<Board, ParseError> {
+        let mut values: Vec<u8> = Vec::with_capacity(81);
+        for (line_number, line) in grid.lines().enumerate() {
+            for (column, character) in visible_chars(line) {
+                match grid_char_to_value(character) {
+                    Ok(Some(value)) => values.push(value),
+                    Ok(None) => continue,
+                    Err(_) => return Err(ParseError::InvalidGridCharacter {
+                        character,
+                        line: line_number + 1,
+                        column: column + 1,
+                    }),
+                }
+            }
+        }
+
+        if values.len() != 81 {
+            return Err(ParseError::InvalidLength { length: values.len() });
+        }
+
+        Ok(values_to_board(&values))
+    }
+}
+
+impl FromStr for Board {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Board::from_line(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EASY: &str = ".4.6.2.31.....16.96..54.827..276..8.5.6....74.87..5.6216..8..5.82...7.9.7....62..";
+
+    #[test]
+    fn parses_dots_and_zeros_as_blanks() {
+        let with_zeros = EASY.replace('.', "0");
+        let a = Board::from_line(EASY).unwrap();
+        let b = Board::from_line(&with_zeros).unwrap();
+        for i in 0..81 {
+            assert_eq!(a.cells[i].value, b.cells[i].value);
+            assert_eq!(a.cells[i].initial, b.cells[i].initial);
+        }
+    }
+
+    #[test]
+    fn marks_givens_as_initial() {
+        let board: Board = EASY.parse().unwrap();
+        assert_eq!(board.cells[0].value, None);
+        assert!(!board.cells[0].initial);
+        assert_eq!(board.cells[1].value, Some(4));
+        assert!(board.cells[1].initial);
+        assert_eq!(board.unsolved_indexes().len(), 43);
+    }
+
+    #[test]
+    fn reports_invalid_character_and_position() {
+        let input = EASY.replacen('4', "x", 1);
+        let error = Board::from_line(&input).unwrap_err();
+        assert_eq!(error, ParseError::InvalidCharacter { character: 'x', position: 1 });
+        let error = Board::from_line(&format!("  {}", input)).unwrap_err();
+        assert_eq!(error, ParseError::InvalidCharacter { character: 'x', position: 3 });
+    }
+
+    #[test]
+    fn parses_ascii_grid() {
+        let grid = "
+            . 4 . | 6 . 2 | . 3 1
+            . . . | . . 1 | 6 . 9
+            6 . . | 5 4 . | 8 2 7
+            ------+-------+------
+            . . 2 | 7 6 . | . 8 .
+            5 . 6 | . . . | . 7 4
+            . 8 7 | . . 5 | . 6 2
+            ------+-------+------
+            1 6 . | . 8 . | . 5 .
+            8 2 . | . . 7 | . 9 .
+            7 . . | . . 6 | 2 . .
+        ";
+        let a = Board::from_grid(grid).unwrap();
+        let b = Board::from_line(EASY).unwrap();
+        for i in 0..81 {
+            assert_eq!(a.cells[i].value, b.cells[i].value);
+        }
+    }
+
+    #[test]
+    fn parses_rendered_board() {
+        let board = Board::from_line(EASY).unwrap();
+        let parsed = Board::from_grid(&board.render()).unwrap();
+        for i in 0..81 {
+            assert_eq!(parsed.cells[i].value, board.cells[i].value);
+        }
+    }
+
+    #[test]
+    fn reports_invalid_grid_character_line_and_column() {
+        let grid = "4.6.2.31.....16.96..54.827\n..276..8.5.6....74.87..5.62\n16..8..5.82...7.9.7....6?..";
+        let error = Board::from_grid(grid).unwrap_err();
+        assert_eq!(error, ParseError::InvalidGridCharacter { character: '?', line: 3, column: 25 });
+
+        // Columns count the escape sequences of coloured output too
+        let coloured = grid.replace("16..", "\u{1b}[1m1\u{1b}[0m6..");
+        let error = Board::from_grid(&coloured).unwrap_err();
+        assert_eq!(error, ParseError::InvalidGridCharacter { character: '?', line: 3, column: 33 });
+    }
+
+    #[test]
+    fn reports_invalid_length() {
+        let error = Board::from_line(&EASY[..80]).unwrap_err();
+        assert_eq!(error, ParseError::InvalidLength { length: 80 });
+    }
+}
@@ -0,0 +1,115 @@
+use std::collections::VecDeque;
+
+use crate::board::Board;
+use crate::board::BoardFragment;
+use crate::board_index::BoardIndex;
+use crate::board_index::BoardLocation;
+use crate::diff::Diff;
+use crate::diff::PatchDiff;
+use crate::error::SolveError;
+
+// Queue of cells waiting to be collapsed, where each cell is queued at most
+// once at a time
+struct WorkQueue {
+    queue: VecDeque<BoardIndex>,
+    queued: [bool; 81],
+}
+
+impl WorkQueue {
+    fn new() -> WorkQueue {
+        WorkQueue { queue: VecDeque::new(), queued: [false; 81] }
+    }
+
+    fn push(&mut self, index: BoardIndex) {
+        let i = index.value as usize;
+        if !self.queued[i] {
+            self.queued[i] = true;
+            self.queue.push_back(index);
+        }
+    }
+
+    fn pop(&mut self) -> Option<BoardIndex> {
+        let index = self.queue.pop_front()?;
+        self.queued[index.value as usize] = false;
+        Some(index)
+    }
+}
+
+impl Board {
+    // Collapses the given cells and follows the changes through the board:
+    // when a cell is fixed its peers are queued, and when a cell loses
+    // options it is queued to be examined again. This repeats until the queue
+    // is empty. All of the changes are returned as one diff. On a
+    // contradiction the changes are reverted before the error is returned.
+    pub fn propagate(&mut self, seeds: Vec<BoardIndex>) -> Result<Diff<BoardFragment>, SolveError> {
+        let mut queue = WorkQueue::new();
+        seeds.into_iter().for_each(|i| queue.push(i));
+
+        let mut changes: Diff<BoardFragment> = Diff::new(vec![]);
+        while let Some(index) = queue.pop() {
+            let diff = match self.collapse_cell(&index) {
+                Ok(diff) => diff,
+                Err(e) => {
+                    self.revert_diff(&changes);
+                    return Err(e);
+                }
+            };
+
+            for fragment in diff.fragments() {
+                let changed = fragment.index();
+                if self.get_cell(changed).value.is_some() {
+                    changed
+                        .entangled_indexes()
+                        .into_iter()
+                        .filter(|i| self.get_cell(i).value.is_none())
+                        .for_each(|i| queue.push(i));
+                } else {
+                    queue.push(changed.clone());
+                }
+            }
+            changes.extend(diff);
+        }
+        Ok(changes)
+    }
+
+    // Propagates from every unsolved cell
+    pub fn propagate_all(&mut self) -> Result<Diff<BoardFragment>, SolveError> {
+        self.propagate(self.unsolved_indexes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EASY: &str = ".4.6.2.31.....16.96..54.827..276..8.5.6....74.87..5.6216..8..5.82...7.9.7....62..";
+
+    #[test]
+    fn solves_easy_puzzle() {
+        let mut board = Board::from_line(EASY).unwrap();
+        board.propagate_all().unwrap();
+        assert!(board.unsolved_indexes().is_empty());
+    }
+
+    #[test]
+    fn stable_board_has_nothing_to_propagate() {
+        let mut board = Board::from_line(EASY).unwrap();
+        board.propagate_all().unwrap();
+        assert!(board.propagate_all().unwrap().is_empty());
+    }
+
+    #[test]
+    fn reverts_changes_on_contradiction() {
+        // Both of the first two cells must be a 2, so fixing the first leaves
+        // nothing for the second
+        let line = format!("..3456789{}1{}1{}", ".".repeat(18), ".".repeat(27), ".".repeat(25));
+        let original = Board::from_line(&line).unwrap();
+        let mut board = original.clone();
+        let error = board.propagate_all().unwrap_err();
+        assert!(matches!(error, SolveError::NoCandidates { row: 0, .. }));
+        for (a, b) in original.cells.iter().zip(board.cells.iter()) {
+            assert_eq!(a.value, b.value);
+            assert_eq!(a.options, b.options);
+        }
+    }
+}
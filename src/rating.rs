@@ -0,0 +1,213 @@
+use std::fmt;
+
+use crate::board::Board;
+use crate::solver::Solver;
+
+// How hard each technique is, from 1 for singles up to 8 for forcing chains.
+// Steps are looked up by the name they were found with, so the variants a
+// technique can report are listed too.
+const WEIGHTS: &[(&str, u32)] = &[
+    ("Naked Single", 1),
+    ("Hidden Single", 1),
+    ("Pointing", 2),
+    ("Claiming", 2),
+    ("Naked Pair", 2),
+    ("Hidden Pair", 3),
+    ("Naked Triple", 3),
+    ("Hidden Triple", 3),
+    ("X-Wing", 4),
+    ("Skyscraper", 4),
+    ("2-String Kite", 4),
+    ("Turbot Fish", 4),
+    ("Empty Rectangle", 4),
+    ("XY-Wing", 4),
+    ("XYZ-Wing", 4),
+    ("W-Wing", 4),
+    ("Swordfish", 5),
+    ("Naked Quad", 5),
+    ("Hidden Quad", 5),
+    ("Color Trap", 5),
+    ("Color Wrap", 5),
+    ("Finned X-Wing", 5),
+    ("Sashimi X-Wing", 5),
+    ("Unique Rectangle Type 1", 5),
+    ("Unique Rectangle Type 2", 5),
+    ("Unique Rectangle Type 3", 5),
+    ("Unique Rectangle Type 4", 5),
+    ("Unique Rectangle Type 5", 5),
+    ("Unique Rectangle Type 6", 5),
+    ("Hidden Unique Rectangle", 5),
+    ("BUG+1", 5),
+    ("Jellyfish", 6),
+    ("Finned Swordfish", 6),
+    ("Sashimi Swordfish", 6),
+    ("Finned Jellyfish", 6),
+    ("Sashimi Jellyfish", 6),
+    ("WXYZ-Wing", 6),
+    ("Sue de Coq", 6),
+    ("X-Chain", 6),
+    ("XY-Chain", 6),
+    ("AIC", 7),
+    ("Continuous Nice Loop", 7),
+    ("Discontinuous Nice Loop", 7),
+    ("ALS-XZ", 7),
+    ("Doubly Linked ALS-XZ", 7),
+    ("ALS-XY-Wing", 7),
+    ("Death Blossom", 7),
+    ("Template", 7),
+    ("Nishio", 8),
+    ("Cell Forcing Chain", 8),
+    ("Region Forcing Chain", 8),
+];
+
+// Techniques this module doesn't know are treated as expert ones
+const UNKNOWN_WEIGHT: u32 = 6;
+
+pub fn technique_weight(technique: &str) -> u32 {
+    WEIGHTS
+        .iter()
+        .find(|(name, _)| *name == technique)
+        .map_or(UNKNOWN_WEIGHT, |(_, weight)| *weight)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Grade {
+    // Singles only
+    Easy,
+    // Intersections, pairs and triples
+    Medium,
+    // Basic fish, single digit patterns, wings and quads
+    Hard,
+    // Anything harder
+    Expert,
+    // The logical techniques get stuck and the puzzle needs guessing
+    RequiresSearch,
+}
+
+impl Grade {
+    fn from_weight(weight: u32) -> Grade {
+        match weight {
+            0..=1 => Grade::Easy,
+            2..=3 => Grade::Medium,
+            4..=5 => Grade::Hard,
+            _ => Grade::Expert,
+        }
+    }
+}
+
+impl fmt::Display for Grade {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Grade::Easy => "easy",
+            Grade::Medium => "medium",
+            Grade::Hard => "hard",
+            Grade::Expert => "expert",
+            Grade::RequiresSearch => "requires search",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rating {
+    pub grade: Grade,
+    // The hardest weight needed times 100, plus one for every step harder
+    // than a single, up to 99. Puzzles that need search score 1000.
+    pub score: u32,
+    // The hardest technique used, which is `None` when no step was needed
+    pub hardest: Option<&'static str>,
+    // How often each technique was used, in the order they were first used
+    pub counts: Vec<(&'static str, usize)>,
+}
+
+impl fmt::Display for Rating {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.grade, self.score)?;
+        if let Some(hardest) = self.hardest {
+            write!(f, ", hardest technique {}", hardest)?;
+        }
+        for (technique, count) in &self.counts {
+            write!(f, "\n  {} × {}", technique, count)?;
+        }
+        Ok(())
+    }
+}
+
+impl Board {
+    // Rates the puzzle by solving a copy of it with the default techniques in
+    // their fixed order. Invalid puzzles are graded as needing search.
+    pub fn rate(&self) -> Rating {
+        let mut board = self.clone();
+        let log = Solver::new().solve_logged(&mut board);
+
+        let mut counts: Vec<(&'static str, usize)> = vec![];
+        let mut hardest: Option<&'static str> = None;
+        let mut solved = false;
+        if let Ok(log) = log {
+            solved = log.solved;
+            for step in &log.steps {
+                match counts.iter_mut().find(|(t, _)| *t == step.technique) {
+                    Some((_, count)) => *count += 1,
+                    None => counts.push((step.technique, 1)),
+                }
+                if hardest.is_none_or(|h| technique_weight(step.technique) > technique_weight(h)) {
+                    hardest = Some(step.technique);
+                }
+            }
+        }
+
+        let weight = hardest.map_or(0, technique_weight);
+        let harder: usize = counts
+            .iter()
+            .filter(|(t, _)| technique_weight(t) > 1)
+            .map(|(_, count)| count)
+            .sum();
+        let (grade, score) = if solved {
+            (Grade::from_weight(weight), weight * 100 + harder.min(99) as u32)
+        } else {
+            (Grade::RequiresSearch, 1000)
+        };
+        Rating { grade, score, hardest, counts }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::techniques;
+    use crate::techniques::Technique;
+
+    #[test]
+    fn weighs_every_builtin_technique() {
+        for technique in techniques::TECHNIQUES.iter().chain(techniques::UNIQUENESS_TECHNIQUES) {
+            assert!(WEIGHTS.iter().any(|(name, _)| *name == technique.name()), "{} has no weight", technique.name());
+        }
+    }
+
+    #[test]
+    fn rates_by_hardest_technique() {
+        let easy = Board::from_line(".4.6.2.31.....16.96..54.827..276..8.5.6....74.87..5.6216..8..5.82...7.9.7....62..").unwrap();
+        let rating = easy.rate();
+        assert_eq!(rating.grade, Grade::Easy);
+        assert_eq!(rating.hardest, Some("Naked Single"));
+        assert_eq!(rating.score, 100);
+        let blanks = easy.cells.iter().filter(|c| c.value.is_none()).count();
+        assert_eq!(rating.counts.iter().map(|(_, c)| c).sum::<usize>(), blanks);
+
+        let harder = Board::from_line("..9748...7.........2.1.9.....7...24..64.1.59..98...3.....8.3.2.........6...2759..").unwrap();
+        let rating = harder.rate();
+        assert_eq!(rating.grade, Grade::Hard);
+        assert_eq!(rating.hardest, Some("Swordfish"));
+        assert!(rating.score > 500 && rating.score < 600);
+    }
+
+    #[test]
+    fn grades_unsolvable_puzzles_as_needing_search() {
+        // A rectangle of 9s and 4s that could go either way
+        let ambiguous = Board::from_line("..8672531275831649631549827..2763185516928374387415962169284753823157496754396218").unwrap();
+        let rating = ambiguous.rate();
+        assert_eq!(rating.grade, Grade::RequiresSearch);
+        assert_eq!(rating.score, 1000);
+        assert_eq!(rating.to_string(), "requires search (1000)");
+    }
+}
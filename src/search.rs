@@ -0,0 +1,239 @@
+use std::fmt;
+
+use crate::board::Board;
+use crate::board::BoardFragment;
+use crate::board_index::BoardIndex;
+use crate::board_index::BoardLocation;
+use crate::diff::Diff;
+use crate::diff::PatchDiff;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SearchStats {
+    // Number of candidates tried as a guess
+    pub guesses: usize,
+    // Number of guesses that led to a contradiction and were reverted
+    pub backtracks: usize,
+}
+
+// The solutions found while counting
+#[derive(Debug, Clone)]
+pub struct SolutionCount {
+    pub solutions: Vec<Board>,
+    // Counting stopped at the cap, so there may be more solutions
+    pub capped: bool,
+}
+
+impl SolutionCount {
+    pub fn count(&self) -> usize {
+        self.solutions.len()
+    }
+
+    pub fn is_unique(&self) -> bool {
+        self.solutions.len() == 1 && !self.capped
+    }
+}
+
+impl fmt::Display for SolutionCount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.solutions.len(), self.capped) {
+            (0, _) => write!(f, "no solution"),
+            (1, false) => write!(f, "unique"),
+            // With a cap of one there's no telling whether there are more
+            (1, true) => write!(f, "at least one solution"),
+            (count, true) => write!(f, "multiple (≥{})", count),
+            (count, false) => write!(f, "multiple ({})", count),
+        }
+    }
+}
+
+impl Board {
+    // The unsolved cell with the fewest options, preferring the lowest index
+    fn most_constrained_index(&self) -> Option<BoardIndex> {
+        self.cells
+            .iter()
+            .filter(|c| c.value.is_none())
+            .min_by_key(|c| c.entropy())
+            .map(|c| c.index.clone())
+    }
+
+    // Sets the cell to the guessed value and removes that value from its
+    // peers' options
+    fn guess(&mut self, index: &BoardIndex, value: u8) -> Diff<BoardFragment> {
+        let diff = self.placement_diff(index, value);
+        self.apply_diff(&diff);
+        diff
+    }
+
+    fn search_recursive(&mut self, seeds: Vec<BoardIndex>, stats: &mut SearchStats) -> bool {
+        let propagated = match self.propagate(seeds) {
+            Ok(diff) => diff,
+            Err(_) => return false,
+        };
+
+        let index = match self.most_constrained_index() {
+            Some(index) => index,
+            None => return true,
+        };
+
+        for value in self.get_cell(&index).options.clone() {
+            stats.guesses += 1;
+            let diff = self.guess(&index, value);
+            if self.search_recursive(index.entangled_indexes(), stats) {
+                return true;
+            }
+            stats.backtracks += 1;
+            self.revert_diff(&diff);
+        }
+
+        self.revert_diff(&propagated);
+        false
+    }
+
+    // Whether two solved cells that see each other have the same value
+    fn has_conflicting_values(&self) -> bool {
+        self.cells.iter().any(|cell| {
+            cell.value.is_some()
+                && cell.index.entangled_indexes().iter().any(|peer| self.get_cell(peer).value == cell.value)
+        })
+    }
+
+    // Like `search_recursive`, but keeps going after a solution, recording
+    // each one, until every guess is tried or the cap is reached. The board
+    // is left as it was.
+    fn count_recursive(&mut self, seeds: Vec<BoardIndex>, cap: usize, solutions: &mut Vec<Board>) {
+        let propagated = match self.propagate(seeds) {
+            Ok(diff) => diff,
+            Err(_) => return,
+        };
+
+        match self.most_constrained_index() {
+            None => solutions.push(self.clone()),
+            Some(index) => {
+                for value in self.get_cell(&index).options.clone() {
+                    if solutions.len() >= cap {
+                        break;
+                    }
+                    let diff = self.guess(&index, value);
+                    self.count_recursive(index.entangled_indexes(), cap, solutions);
+                    self.revert_diff(&diff);
+                }
+            }
+        }
+
+        self.revert_diff(&propagated);
+    }
+
+    // Counts the solutions of the puzzle, stopping once `cap` are found. A
+    // cap of 2 is enough to tell whether a puzzle is unique. The board isn't
+    // changed.
+    pub fn count_solutions(&self, cap: usize) -> SolutionCount {
+        let mut solutions = vec![];
+        if cap > 0 && !self.has_conflicting_values() {
+            let mut board = self.clone();
+            board.count_recursive(board.unsolved_indexes(), cap, &mut solutions);
+        }
+        let capped = solutions.len() >= cap;
+        SolutionCount { solutions, capped }
+    }
+
+    // Solves the board by propagating constraints and guessing the most
+    // constrained cell whenever propagation gets stuck, reverting back to the
+    // last guess on a contradiction. Returns `None` if the puzzle has no
+    // solution, in which case the board is left unchanged.
+    pub fn search(&mut self) -> Option<SearchStats> {
+        // Propagation only looks at unsolved cells, so it would never notice
+        if self.has_conflicting_values() {
+            return None;
+        }
+        let mut stats = SearchStats::default();
+        if self.search_recursive(self.unsolved_indexes(), &mut stats) {
+            Some(stats)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Known to need guessing: "AI Escargot"
+    const HARD: &str = "1....7.9..3..2...8..96..5....53..9...1..8...26....4...3......1..4......7..7...3..";
+
+    fn assert_valid_solution(board: &Board) {
+        for cell in board.cells.iter() {
+            let value = cell.value.expect("cell should be solved");
+            for peer in cell.index.entangled_indexes() {
+                assert_ne!(board.get_cell(&peer).value, Some(value));
+            }
+        }
+    }
+
+    #[test]
+    fn solves_puzzle_that_needs_guessing() {
+        let mut board = Board::from_line(HARD).unwrap();
+        assert!(!board.clone().solve().unwrap());
+        let stats = board.search().unwrap();
+        assert!(stats.guesses > 0);
+        assert_valid_solution(&board);
+    }
+
+    #[test]
+    fn keeps_givens() {
+        let original = Board::from_line(HARD).unwrap();
+        let mut board = original.clone();
+        board.search().unwrap();
+        for (a, b) in original.cells.iter().zip(board.cells.iter()) {
+            if a.initial {
+                assert_eq!(a.value, b.value);
+            }
+        }
+    }
+
+    #[test]
+    fn counts_solutions_up_to_the_cap() {
+        let unique = Board::from_line(HARD).unwrap().count_solutions(2);
+        assert!(unique.is_unique());
+        assert_eq!(unique.to_string(), "unique");
+        assert_valid_solution(&unique.solutions[0]);
+
+        // The 4s and 9s of a rectangle could go either way
+        let line = "..8672531275831649631549827..2763185516928374387415962169284753823157496754396218";
+        let board = Board::from_line(line).unwrap();
+        let two = board.count_solutions(10);
+        assert_eq!((two.count(), two.capped), (2, false));
+        assert_eq!(two.to_string(), "multiple (2)");
+        assert_ne!(two.solutions[0].cells[0].value, two.solutions[1].cells[0].value);
+        assert_eq!(board.count_solutions(1).to_string(), "at least one solution");
+
+        let empty = Board::from_line(&".".repeat(81)).unwrap().count_solutions(5);
+        assert_eq!(empty.to_string(), "multiple (≥5)");
+    }
+
+    #[test]
+    fn counts_no_solutions_for_conflicting_givens() {
+        let line = format!("11{}", ".".repeat(79));
+        assert_eq!(Board::from_line(&line).unwrap().count_solutions(2).to_string(), "no solution");
+    }
+
+    #[test]
+    fn finds_no_solution_for_conflicting_givens() {
+        let line = format!("11{}", ".".repeat(79));
+        let mut board = Board::from_line(&line).unwrap();
+        assert_eq!(board.search(), None);
+    }
+
+    #[test]
+    fn leaves_unsolvable_board_unchanged() {
+        // The first cell needs a 1 or a 9, but both are already in its box
+        let line = format!("..23456789{}1{}", ".".repeat(8), ".".repeat(62));
+        let original = Board::from_line(&line).unwrap();
+        let mut board = original.clone();
+        assert_eq!(board.search(), None);
+        for (a, b) in original.cells.iter().zip(board.cells.iter()) {
+            assert_eq!(a.value, b.value);
+            assert_eq!(a.options, b.options);
+        }
+    }
+}
@@ -0,0 +1,321 @@
+use std::fmt;
+
+use crate::board::Board;
+use crate::board::BoardFragment;
+use crate::diff::Diff;
+use crate::diff::PatchDiff;
+use crate::error::SolveError;
+use crate::techniques;
+use crate::techniques::Step;
+use crate::techniques::Technique;
+use crate::techniques::TECHNIQUES;
+use crate::techniques::UNIQUENESS_TECHNIQUES;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolverError {
+    // No builtin technique has the given name
+    UnknownTechnique { name: String },
+}
+
+impl fmt::Display for SolverError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolverError::UnknownTechnique { name } => write!(f, "unknown technique {:?}", name),
+        }
+    }
+}
+
+impl std::error::Error for SolverError {}
+
+// Every step a solve took, in order
+#[derive(Debug, Clone)]
+pub struct SolveLog {
+    pub steps: Vec<Step>,
+    pub solved: bool,
+}
+
+impl fmt::Display for SolveLog {
+    // One numbered line per step, with any proof indented below it
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (n, step) in self.steps.iter().enumerate() {
+            writeln!(f, "{}. {}", n + 1, step.explanation())?;
+            for line in &step.proof {
+                writeln!(f, "   {}", line)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// An ordered list of techniques. At every step the first technique in the
+// list that finds one is used, so even naked singles are only placed when
+// "Naked Single" is in the list. When it comes first they're placed by
+// propagating constraints, which finds them all without searching the board
+// for each one.
+pub struct Solver {
+    techniques: Vec<Box<dyn Technique>>,
+}
+
+impl Solver {
+    // Every builtin technique that doesn't assume uniqueness, from the
+    // cheapest to the most expensive
+    pub fn new() -> Solver {
+        let mut solver = Solver::empty();
+        for technique in TECHNIQUES {
+            solver.add(*technique);
+        }
+        solver
+    }
+
+    pub fn empty() -> Solver {
+        Solver { techniques: vec![] }
+    }
+
+    // Every builtin technique, with the ones that assume uniqueness last.
+    // Only use this on puzzles known to have a single solution.
+    pub fn assuming_unique() -> Solver {
+        let mut solver = Solver::new();
+        solver.assume_unique();
+        solver
+    }
+
+    // The named builtin techniques, in the given order
+    pub fn from_names(names: &[&str]) -> Result<Solver, SolverError> {
+        let mut solver = Solver::empty();
+        for name in names {
+            match techniques::builtin(name) {
+                Some(technique) => solver.add(technique),
+                None => return Err(SolverError::UnknownTechnique { name: name.to_string() }),
+            };
+        }
+        Ok(solver)
+    }
+
+    // Adds the technique after all the others
+    pub fn add<T: Technique + 'static>(&mut self, technique: T) -> &mut Solver {
+        self.techniques.push(Box::new(technique));
+        self
+    }
+
+    // Adds the technique at the given position, moving the later ones back
+    pub fn insert<T: Technique + 'static>(&mut self, position: usize, technique: T) -> &mut Solver {
+        self.techniques.insert(position, Box::new(technique));
+        self
+    }
+
+    // Adds the builtin techniques that assume uniqueness after all the
+    // others, skipping any already in the list
+    pub fn assume_unique(&mut self) -> &mut Solver {
+        for technique in UNIQUENESS_TECHNIQUES {
+            if !self.names().iter().any(|n| techniques::same_name(n, technique.name())) {
+                self.add(*technique);
+            }
+        }
+        self
+    }
+
+    // Removes every technique with the name
+    pub fn remove(&mut self, name: &str) -> &mut Solver {
+        self.techniques.retain(|t| !techniques::same_name(t.name(), name));
+        self
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.techniques.iter().map(|t| t.name()).collect()
+    }
+
+    pub fn assumes_unique(&self) -> bool {
+        self.techniques.iter().any(|t| t.assumes_unique())
+    }
+
+    // Finds a step using the first technique that applies
+    pub fn next_step(&self, board: &Board) -> Option<Step> {
+        self.techniques.iter().find_map(|t| t.find_step(board))
+    }
+
+    // Applies the techniques until the board is solved or none of them finds
+    // a step. Returns whether the board was solved.
+    pub fn solve(&self, board: &mut Board) -> Result<bool, SolveError> {
+        self.run(board, |_| {})
+    }
+
+    // Like `solve`, recording every step taken
+    pub fn solve_logged(&self, board: &mut Board) -> Result<SolveLog, SolveError> {
+        let mut steps = vec![];
+        let solved = self.run(board, |step| steps.push(step))?;
+        Ok(SolveLog { steps, solved })
+    }
+
+    // Whether naked singles are placed by propagation, which takes the same
+    // steps as long as "Naked Single" is the first technique tried
+    fn propagates(&self) -> bool {
+        self.techniques.first().is_some_and(|t| t.name() == "Naked Single")
+    }
+
+    // Solves as `solve` describes, handing every step taken to `taken`
+    fn run<F>(&self, board: &mut Board, mut taken: F) -> Result<bool, SolveError>
+    where
+        F: FnMut(Step),
+    {
+        board.prune_options()?;
+        let mut seeds = board.unsolved_indexes();
+        loop {
+            if self.propagates() {
+                let propagated = board.propagate(seeds)?;
+                naked_singles(&propagated).into_iter().for_each(&mut taken);
+            }
+            if board.unsolved_indexes().is_empty() {
+                break;
+            }
+            let step = match self.next_step(board) {
+                Some(step) => step,
+                None => break,
+            };
+            board.apply_diff(&step.diff);
+            seeds = step.diff.fragments().iter().map(|f| f.index().clone()).collect();
+            taken(step);
+        }
+        Ok(board.unsolved_indexes().is_empty())
+    }
+}
+
+// Splits the changes propagation made into a "Naked Single" step for each
+// placement, together with the candidates it removed from its peers
+fn naked_singles(propagated: &Diff<BoardFragment>) -> Vec<Step> {
+    let mut steps: Vec<Step> = vec![];
+    let mut unplaced: Vec<BoardFragment> = vec![];
+    for fragment in propagated.fragments() {
+        match (fragment.fragment().placed(), steps.last_mut()) {
+            (Some(value), _) => {
+                let fragments = [std::mem::take(&mut unplaced), vec![fragment.clone()]].concat();
+                let cells = vec![fragment.index().clone()];
+                steps.push(Step::new("Naked Single", cells, vec![value], vec![], Diff::new(fragments)));
+            }
+            (None, Some(step)) => step.diff.extend(Diff::new(vec![fragment.clone()])),
+            (None, None) => unplaced.push(fragment.clone()),
+        }
+    }
+    steps
+}
+
+impl Board {
+    // Solves the board with every builtin technique that holds for any
+    // puzzle. Returns whether the board was solved.
+    pub fn solve(&mut self) -> Result<bool, SolveError> {
+        Solver::new().solve(self)
+    }
+
+    // Like `solve`, but also uses deductions that are only valid when the
+    // puzzle has a single solution. On a puzzle with several solutions this
+    // can remove every solution.
+    pub fn solve_assuming_unique(&mut self) -> Result<bool, SolveError> {
+        Solver::assuming_unique().solve(self)
+    }
+}
+
+impl Default for Solver {
+    fn default() -> Solver {
+        Solver::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board_index::BoardIndex;
+
+    const PUZZLE: &str = ".4.6.2.31.....16.96..54.827..276..8.5.6....74.87..5.6216..8..5.82...7.9.7....62..";
+
+    // Places a 1 in the first unsolved cell that can take it
+    struct FirstOne;
+
+    impl Technique for FirstOne {
+        fn name(&self) -> &'static str {
+            "First One"
+        }
+
+        fn find_step(&self, board: &Board) -> Option<Step> {
+            let index = board.unsolved_indexes().into_iter().find(|i| board.has_candidate(i, 1))?;
+            Some(Step::new(self.name(), vec![index.clone()], vec![1], vec![], board.placement_diff(&index, 1)))
+        }
+    }
+
+    #[test]
+    fn configures_builtin_techniques_by_name() {
+        let mut solver = Solver::from_names(&["hidden single", "X-Wing", "bug+1"]).unwrap();
+        assert_eq!(solver.names(), vec!["Hidden Single", "X-Wing", "BUG+1"]);
+        assert!(solver.assumes_unique());
+        solver.remove("BUG+1");
+        assert!(!solver.assumes_unique());
+        assert_eq!(
+            Solver::from_names(&["Naked Single", "Guessing"]).err(),
+            Some(SolverError::UnknownTechnique { name: "Guessing".to_string() })
+        );
+        assert_eq!(Solver::new().names().len(), TECHNIQUES.len());
+    }
+
+    #[test]
+    fn uses_techniques_in_order() {
+        let mut board = Board::from_line(PUZZLE).unwrap();
+        board.prune_options().unwrap();
+        let mut solver = Solver::new();
+        assert_eq!(solver.next_step(&board).unwrap().technique, "Naked Single");
+
+        solver.insert(0, FirstOne);
+        let step = solver.next_step(&board).unwrap();
+        assert_eq!(step.technique, "First One");
+        assert_eq!(step.cells, vec![BoardIndex::new(19).unwrap()]);
+    }
+
+    #[test]
+    fn solves_with_the_configured_techniques() {
+        let mut board = Board::from_line(PUZZLE).unwrap();
+        assert!(Solver::from_names(&["Hidden Single"]).unwrap().solve(&mut board).unwrap());
+
+        // Naked singles aren't placed unless they're asked for
+        let mut board = Board::from_line(PUZZLE).unwrap();
+        let blanks = board.unsolved_indexes().len();
+        assert!(!Solver::empty().solve(&mut board).unwrap());
+        assert_eq!(board.unsolved_indexes().len(), blanks);
+    }
+
+    #[test]
+    fn reports_contradictions_found_while_propagating() {
+        // Both of the first two cells must be a 2, which only shows once the
+        // first is placed
+        let line = format!("..3456789{}1{}1{}", ".".repeat(18), ".".repeat(27), ".".repeat(25));
+        let mut board = Board::from_line(&line).unwrap();
+        assert!(matches!(Solver::new().solve(&mut board), Err(SolveError::NoCandidates { row: 0, .. })));
+    }
+
+    #[test]
+    fn adds_uniqueness_techniques_once() {
+        let mut solver = Solver::from_names(&["Naked Single", "BUG+1"]).unwrap();
+        solver.assume_unique();
+        assert_eq!(solver.names().len(), 1 + UNIQUENESS_TECHNIQUES.len());
+        assert_eq!(solver.names()[1], "BUG+1");
+        assert_eq!(Solver::assuming_unique().names().len(), TECHNIQUES.len() + UNIQUENESS_TECHNIQUES.len());
+    }
+
+    #[test]
+    fn logs_every_step() {
+        let mut board = Board::from_line(PUZZLE).unwrap();
+        let log = Solver::new().solve_logged(&mut board).unwrap();
+        assert!(log.solved);
+        assert_eq!(log.steps.len(), board.cells.iter().filter(|c| !c.initial).count());
+        let text = log.to_string();
+        assert_eq!(text.lines().count(), log.steps.len());
+        assert!(text.starts_with("1. Naked single: r1c1 can only be 9\n"));
+    }
+
+    #[test]
+    fn logs_every_placement_without_naked_singles() {
+        let mut board = Board::from_line(PUZZLE).unwrap();
+        let blanks = board.unsolved_indexes().len();
+        let log = Solver::from_names(&["Hidden Single", "X-Wing"]).unwrap().solve_logged(&mut board).unwrap();
+        assert!(log.solved);
+        let placements: usize = log.steps.iter().map(|s| s.placements().len()).sum();
+        assert_eq!(placements, blanks);
+        assert!(log.steps.iter().all(|s| s.technique != "Naked Single"));
+    }
+}
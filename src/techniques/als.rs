@@ -0,0 +1,453 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::rc::Rc;
+use std::sync::OnceLock;
+
+use crate::board::Board;
+use crate::board_index::BoardIndex;
+use crate::board_index::House;
+use crate::techniques::bit_digits;
+use crate::techniques::combinations;
+use crate::techniques::peers;
+use crate::techniques::CellMask;
+use crate::techniques::Step;
+
+fn cell_mask(cells: &[BoardIndex]) -> CellMask {
+    cells.iter().fold(0, |mask, i| mask | 1 << i.value)
+}
+
+// A set of digits as bits, with bit n for the digit n
+fn digit_bits(digits: &[u8]) -> u16 {
+    digits.iter().fold(0, |bits, d| bits | 1 << d)
+}
+
+fn mask_cells(mask: CellMask) -> Vec<BoardIndex> {
+    (0..81).filter(|n| mask & 1 << n != 0).map(|n| BoardIndex::new(n).unwrap()).collect()
+}
+
+// The peers of the cell as a mask, worked out once for the whole board
+fn peer_mask(index: u8) -> CellMask {
+    static PEER_MASKS: OnceLock<Vec<CellMask>> = OnceLock::new();
+    let masks = PEER_MASKS.get_or_init(|| {
+        (0..81).map(|i| cell_mask(peers(&BoardIndex::new(i).unwrap()))).collect()
+    });
+    masks[index as usize]
+}
+
+// The cells that see every cell in the mask
+fn seen_by_all(mask: CellMask) -> CellMask {
+    (0..81)
+        .filter(|n| mask & 1 << n != 0)
+        .fold((1 << 81) - 1, |seen, n| seen & peer_mask(n))
+}
+
+// The unsolved cells with each digit as a candidate
+fn candidate_masks(board: &Board) -> [CellMask; 9] {
+    std::array::from_fn(|d| {
+        (0..81)
+            .filter(|n| board.has_candidate(&BoardIndex::new(*n).unwrap(), d as u8 + 1))
+            .fold(0, |mask, n| mask | 1 << n)
+    })
+}
+
+// N unsolved cells in a house with N + 1 candidates between them. Removing
+// any one of the digits would leave a locked set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Als {
+    pub house: House,
+    pub cells: Vec<BoardIndex>,
+    pub digits: Vec<u8>,
+    // The cells of the set, the cells with each digit and the cells that see
+    // every cell with each digit
+    mask: CellMask,
+    digit_masks: [CellMask; 9],
+    seen_masks: [CellMask; 9],
+}
+
+impl Als {
+    fn new(board: &Board, house: House, cells: Vec<BoardIndex>, digits: Vec<u8>) -> Als {
+        let digit_masks = std::array::from_fn(|d| {
+            let with: Vec<BoardIndex> = cells.iter().filter(|i| board.has_candidate(i, d as u8 + 1)).cloned().collect();
+            cell_mask(&with)
+        });
+        let seen_masks = digit_masks.map(seen_by_all);
+        Als { house, mask: cell_mask(&cells), cells, digits, digit_masks, seen_masks }
+    }
+
+    // The cells of the set that have the digit as a candidate
+    pub fn cells_with(&self, digit: u8) -> Vec<BoardIndex> {
+        mask_cells(self.digit_mask(digit))
+    }
+
+    fn digit_mask(&self, digit: u8) -> CellMask {
+        self.digit_masks[digit as usize - 1]
+    }
+
+    fn seen_mask(&self, digit: u8) -> CellMask {
+        self.seen_masks[digit as usize - 1]
+    }
+
+    pub fn overlaps(&self, other: &Als) -> bool {
+        self.mask & other.mask != 0
+    }
+}
+
+// The candidates of every cell, which identify the board state the sets were
+// found on
+type CandidateKey = Vec<Vec<u8>>;
+
+fn candidate_key(board: &Board) -> CandidateKey {
+    board.cells.iter().map(|c| board.candidates(&c.index).to_vec()).collect()
+}
+
+thread_local! {
+    static ALS_CACHE: RefCell<Option<(CandidateKey, Rc<Vec<Als>>)>> = const { RefCell::new(None) };
+}
+
+fn find_almost_locked_sets(board: &Board) -> Vec<Als> {
+    let mut found: Vec<Als> = vec![];
+    let mut seen: HashSet<CellMask> = HashSet::new();
+    for house in House::all() {
+        let unsolved: Vec<BoardIndex> = house
+            .indexes()
+            .into_iter()
+            .filter(|i| !board.candidates(i).is_empty())
+            .collect();
+        for size in 1..unsolved.len() {
+            for cells in combinations(&unsolved, size) {
+                let mut digits: Vec<u8> = cells.iter().flat_map(|i| board.candidates(i).to_vec()).collect();
+                digits.sort();
+                digits.dedup();
+                // A set of cells in a row and a box is only kept once
+                if digits.len() != size + 1 || !seen.insert(cell_mask(&cells)) {
+                    continue;
+                }
+                found.push(Als::new(board, house, cells, digits));
+            }
+        }
+    }
+    found
+}
+
+// Every almost locked set on the board, by house and then by size. The sets
+// are kept until the candidates change, so techniques run one after another
+// on the same board share them.
+pub fn almost_locked_sets(board: &Board) -> Rc<Vec<Als>> {
+    let key = candidate_key(board);
+    ALS_CACHE.with(|cache| {
+        if let Some((cached_key, sets)) = cache.borrow().as_ref() {
+            if *cached_key == key {
+                return sets.clone();
+            }
+        }
+        let sets = Rc::new(find_almost_locked_sets(board));
+        *cache.borrow_mut() = Some((key, sets.clone()));
+        sets
+    })
+}
+
+// Digits in both sets where every cell with the digit in one set sees every
+// cell with it in the other. At most one of the sets can hold the digit.
+fn restricted_commons(a: &Als, b: &Als) -> Vec<u8> {
+    a.digits
+        .iter()
+        .filter(|d| b.digits.contains(d))
+        .filter(|d| b.digit_mask(**d) & !a.seen_mask(**d) == 0)
+        .cloned()
+        .collect()
+}
+
+// The unsolved cells outside of the sets with the digit that see every cell
+// of the sets with the digit
+fn targets(candidates: &[CellMask; 9], sets: &[&Als], digit: u8) -> CellMask {
+    let seen = sets.iter().fold(candidates[digit as usize - 1], |mask, s| mask & s.seen_mask(digit));
+    sets.iter().fold(seen, |mask, s| mask & !s.mask)
+}
+
+// Removes the digit from every target cell
+fn eliminate_seen_by_all(candidates: &[CellMask; 9], sets: &[&Als], digit: u8) -> Vec<(BoardIndex, u8)> {
+    mask_cells(targets(candidates, sets, digit)).into_iter().map(|i| (i, digit)).collect()
+}
+
+fn push_new(eliminations: &mut Vec<(BoardIndex, u8)>, found: Vec<(BoardIndex, u8)>) {
+    for elimination in found {
+        if !eliminations.contains(&elimination) {
+            eliminations.push(elimination);
+        }
+    }
+}
+
+fn als_step(
+    board: &Board,
+    technique: &'static str,
+    sets: &[&Als],
+    mut digits: Vec<u8>,
+    eliminations: &[(BoardIndex, u8)],
+) -> Step {
+    let mut cells: Vec<BoardIndex> = sets.iter().flat_map(|s| s.cells.clone()).collect();
+    cells.sort();
+    cells.dedup();
+    digits.sort();
+    digits.dedup();
+    Step::new(
+        technique,
+        cells,
+        digits,
+        sets.iter().map(|s| s.house).collect(),
+        board.elimination_diff(eliminations),
+    )
+}
+
+// Two sets with a restricted common digit x, which only one of them can
+// hold, so the other is locked. Any other digit z in both sets must then be
+// in one of them, and can be removed from cells that see every z in both.
+// With two restricted common digits both sets are locked, so each of their
+// digits can be removed from cells that see all of that digit in its set.
+pub fn find_als_xz(board: &Board) -> Option<Step> {
+    let sets = almost_locked_sets(board);
+    let candidates = candidate_masks(board);
+    for (n, a) in sets.iter().enumerate() {
+        for b in sets[n + 1..].iter().filter(|b| !a.overlaps(b)) {
+            let restricted = restricted_commons(a, b);
+            if restricted.is_empty() {
+                continue;
+            }
+            let mut eliminations: Vec<(BoardIndex, u8)> = vec![];
+            if restricted.len() == 1 {
+                for z in a.digits.iter().filter(|d| b.digits.contains(d) && !restricted.contains(d)) {
+                    push_new(&mut eliminations, eliminate_seen_by_all(&candidates, &[a, b], *z));
+                }
+            } else {
+                for x in &restricted {
+                    push_new(&mut eliminations, eliminate_seen_by_all(&candidates, &[a, b], *x));
+                }
+                for set in [a, b] {
+                    for z in set.digits.iter().filter(|d| !restricted.contains(d)) {
+                        push_new(&mut eliminations, eliminate_seen_by_all(&candidates, &[set], *z));
+                    }
+                }
+            }
+            if eliminations.is_empty() {
+                continue;
+            }
+            let technique = if restricted.len() == 1 { "ALS-XZ" } else { "Doubly Linked ALS-XZ" };
+            let digits: Vec<u8> = [restricted, eliminations.iter().map(|(_, d)| *d).collect()].concat();
+            return Some(als_step(board, technique, &[a, b], digits, &eliminations));
+        }
+    }
+    None
+}
+
+// A pivot set C shares a restricted common digit x with set A and a
+// different one y with set B. If A doesn't hold a digit z then it's locked
+// and holds x, so C can't hold x and must hold y, leaving B to hold z. A z
+// in both A and B can be removed from cells that see every z in A and B.
+// Picks different restricted digits x and y for the A and B links, leaving
+// at least one digit z. The digits are given as bits.
+fn wing_digits(a_restricted: u16, b_restricted: u16, zs: u16) -> Option<(u8, u8)> {
+    for x in (1..=9).filter(|x| a_restricted & 1 << x != 0) {
+        for y in (1..=9).filter(|y| *y != x && b_restricted & 1 << y != 0) {
+            if zs & !(1 << x) & !(1 << y) != 0 {
+                return Some((x, y));
+            }
+        }
+    }
+    None
+}
+
+pub fn find_als_xy_wing(board: &Board) -> Option<Step> {
+    let sets = almost_locked_sets(board);
+    let candidates = candidate_masks(board);
+    let count = sets.len();
+    let words = count.div_ceil(64);
+
+    // The restricted common digits as bits of every pair of sets that has
+    // any, and the sets linked to each set as bits, so the pivots linked to
+    // both A and B can be found without going through every set
+    let mut restricted: HashMap<(usize, usize), u16> = HashMap::new();
+    let mut linked: Vec<Vec<u64>> = vec![vec![0; words]; count];
+    for (i, a) in sets.iter().enumerate() {
+        for (j, b) in sets.iter().enumerate().skip(i + 1).filter(|(_, b)| !a.overlaps(b)) {
+            let digits = digit_bits(&restricted_commons(a, b));
+            if digits != 0 {
+                restricted.insert((i, j), digits);
+                restricted.insert((j, i), digits);
+                linked[i][j / 64] |= 1 << (j % 64);
+                linked[j][i / 64] |= 1 << (i % 64);
+            }
+        }
+    }
+
+    for (i, a) in sets.iter().enumerate() {
+        for (j, b) in sets.iter().enumerate().skip(i + 1).filter(|(_, b)| !a.overlaps(b)) {
+            // Digits in both sets that would eliminate something
+            let zs = (1..=9u8)
+                .filter(|z| a.digit_mask(*z) != 0 && b.digit_mask(*z) != 0)
+                .filter(|z| targets(&candidates, &[a, b], *z) != 0)
+                .fold(0u16, |bits, z| bits | 1 << z);
+            if zs == 0 {
+                continue;
+            }
+            for (word, (a_links, b_links)) in linked[i].iter().zip(linked[j].iter()).enumerate() {
+                let mut pivots = a_links & b_links;
+                while pivots != 0 {
+                    let c = word * 64 + pivots.trailing_zeros() as usize;
+                    pivots &= pivots - 1;
+                    let (x, y) = match wing_digits(restricted[&(i, c)], restricted[&(j, c)], zs) {
+                        Some(digits) => digits,
+                        None => continue,
+                    };
+                    let remaining = bit_digits(zs & !(1 << x) & !(1 << y));
+                    let mut eliminations: Vec<(BoardIndex, u8)> = vec![];
+                    for z in &remaining {
+                        push_new(&mut eliminations, eliminate_seen_by_all(&candidates, &[a, b], *z));
+                    }
+                    let digits: Vec<u8> = [vec![x, y], remaining].concat();
+                    return Some(als_step(board, "ALS-XY-Wing", &[a, b, &sets[c]], digits, &eliminations));
+                }
+            }
+        }
+    }
+    None
+}
+
+// A stem cell where every candidate d has a petal set holding d in cells
+// that all see the stem. Whichever digit the stem is, that petal loses it
+// and is locked, so a digit z in every petal (and not in the stem) can be
+// removed from cells that see every z in the petals.
+pub fn find_death_blossom(board: &Board) -> Option<Step> {
+    let sets = almost_locked_sets(board);
+    let candidates = candidate_masks(board);
+    for stem in board.unsolved_indexes() {
+        let stem_digits = board.candidates(&stem);
+        if stem_digits.len() < 2 || stem_digits.len() > 3 {
+            continue;
+        }
+        let stem_peers = peer_mask(stem.value);
+        for z in (1..=9).filter(|z| !stem_digits.contains(z)) {
+            let petals: Vec<Vec<&Als>> = stem_digits
+                .iter()
+                .map(|d| {
+                    sets.iter()
+                        .filter(|s| s.mask & 1 << stem.value == 0)
+                        .filter(|s| s.digits.contains(d) && s.digits.contains(&z))
+                        .filter(|s| s.digit_mask(*d) & !stem_peers == 0)
+                        .collect()
+                })
+                .collect();
+            let targets = candidates[z as usize - 1] & !(1 << stem.value);
+            let mut blossom = Blossom { petals: &petals, z, chosen: vec![] };
+            if let Some(targets) = blossom.find(targets) {
+                let sets = blossom.chosen;
+                let eliminations: Vec<(BoardIndex, u8)> = mask_cells(targets).into_iter().map(|i| (i, z)).collect();
+                let digits: Vec<u8> = [stem_digits.to_vec(), vec![z]].concat();
+                let mut step = als_step(board, "Death Blossom", &sets, digits, &eliminations);
+                step.cells.insert(0, stem.clone());
+                return Some(step);
+            }
+        }
+    }
+    None
+}
+
+// Picks a petal for each stem digit in turn, keeping the petals apart and
+// the cells that see every z in them
+struct Blossom<'a> {
+    petals: &'a [Vec<&'a Als>],
+    z: u8,
+    chosen: Vec<&'a Als>,
+}
+
+impl<'a> Blossom<'a> {
+    fn find(&mut self, targets: CellMask) -> Option<CellMask> {
+        if targets == 0 {
+            return None;
+        }
+        if self.chosen.len() == self.petals.len() {
+            return Some(targets);
+        }
+        for petal in &self.petals[self.chosen.len()] {
+            if self.chosen.iter().any(|p| p.overlaps(petal)) {
+                continue;
+            }
+            let remaining = targets & petal.seen_mask(self.z) & !petal.mask;
+            self.chosen.push(petal);
+            if let Some(found) = self.find(remaining) {
+                return Some(found);
+            }
+            self.chosen.pop();
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::techniques::tests::board_with_options;
+
+    fn eliminated(step: &Step) -> Vec<u8> {
+        step.diff.fragments().iter().map(|f| f.index().value).collect()
+    }
+
+    fn cells(indexes: &[u8]) -> Vec<BoardIndex> {
+        indexes.iter().map(|i| BoardIndex::new(*i).unwrap()).collect()
+    }
+
+    #[test]
+    fn finds_almost_locked_sets() {
+        // r1c1 and r1c2 have {1, 2, 3} between them, and r1c1 alone is one
+        let board = board_with_options(&[(0, &[1, 2]), (1, &[2, 3])]);
+        let sets = almost_locked_sets(&board);
+        let found = |house: House, indexes: &[u8], digits: &[u8]| {
+            sets.iter().any(|s| s.house == house && s.cells == cells(indexes) && s.digits == digits)
+        };
+        assert!(found(House::Box(0), &[0], &[1, 2]));
+        assert!(found(House::Box(0), &[0, 1], &[1, 2, 3]));
+        // Found in box 1 first, so not repeated for row 1
+        assert!(!sets.iter().any(|s| s.house == House::Row(0) && s.cells == cells(&[0, 1])));
+    }
+
+    #[test]
+    fn reuses_sets_for_the_same_candidates() {
+        let board = board_with_options(&[(0, &[1, 2]), (1, &[2, 3])]);
+        let first = almost_locked_sets(&board);
+        assert!(Rc::ptr_eq(&first, &almost_locked_sets(&board.clone())));
+
+        let changed = board_with_options(&[(0, &[1, 2]), (1, &[2, 4])]);
+        assert!(!Rc::ptr_eq(&first, &almost_locked_sets(&changed)));
+    }
+
+    #[test]
+    fn finds_als_xz() {
+        // A is r1c1 {1, 2} and B is r1c5 {1, 3} and r2c5 {2, 3}... with 1
+        // restricted between r1c1 and r1c5, one of A or B holds the 2
+        let board = board_with_options(&[(0, &[1, 2]), (4, &[1, 3]), (13, &[2, 3])]);
+        let step = find_als_xz(&board).unwrap();
+        assert_eq!(step.technique, "ALS-XZ");
+        // Cells that see r1c1 and r2c5: the rest of row 1 in box 2, and row 2
+        // in box 1
+        assert_eq!(eliminated(&step), vec![3, 5, 9, 10, 11]);
+    }
+
+    #[test]
+    fn finds_als_xy_wing() {
+        // A r1c1 {1, 4}, pivot C r1c5 {1, 2}, B r5c5 {2, 4}: either r1c1 or
+        // r5c5 is a 4
+        let board = board_with_options(&[(0, &[1, 4]), (4, &[1, 2]), (40, &[2, 4])]);
+        let step = find_als_xy_wing(&board).unwrap();
+        assert_eq!(step.technique, "ALS-XY-Wing");
+        assert_eq!(eliminated(&step), vec![36]);
+    }
+
+    #[test]
+    fn finds_death_blossom() {
+        // The stem r5c5 {1, 2} sees the petals r1c5 {1, 3} and r5c1 {2, 3},
+        // so one of them is a 3
+        let board = board_with_options(&[(40, &[1, 2]), (4, &[1, 3]), (36, &[2, 3])]);
+        let step = find_death_blossom(&board).unwrap();
+        assert_eq!(step.cells[0].value, 40);
+        assert_eq!(eliminated(&step), vec![0]);
+    }
+}
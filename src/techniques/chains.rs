@@ -0,0 +1,364 @@
+use std::collections::VecDeque;
+
+use crate::board::Board;
+use crate::board_index::BoardIndex;
+use crate::board_index::House;
+use crate::techniques::peers;
+use crate::techniques::sees;
+use crate::techniques::subsets::positions;
+use crate::techniques::Step;
+
+// The longest chain searched for, counted in candidates
+pub const MAX_CHAIN_NODES: usize = 16;
+
+// A candidate, meaning a digit in a cell
+pub type Node = (BoardIndex, u8);
+
+// Which links a chain may use
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChainKind {
+    // A single digit, with strong links between the only two cells in a house
+    X,
+    // Strong links within cells with two candidates, and weak links between
+    // cells with the same digit
+    XY,
+    // Any strong or weak link
+    Alternating,
+}
+
+// Two candidates where at least one must be true
+fn strong_links(board: &Board, kind: ChainKind, node: &Node) -> Vec<Node> {
+    let (cell, digit) = node;
+    let mut links: Vec<Node> = vec![];
+    if kind != ChainKind::X {
+        let candidates = board.candidates(cell);
+        if candidates.len() == 2 {
+            let other = if candidates[0] == *digit { candidates[1] } else { candidates[0] };
+            links.push((cell.clone(), other));
+        }
+    }
+    if kind != ChainKind::XY {
+        for house in House::all().into_iter().filter(|h| h.contains(cell)) {
+            let cells = positions(board, &house, *digit);
+            if cells.len() == 2 {
+                let other = if cells[0] == *cell { &cells[1] } else { &cells[0] };
+                if !links.iter().any(|(i, d)| i == other && d == digit) {
+                    links.push((other.clone(), *digit));
+                }
+            }
+        }
+    }
+    links
+}
+
+// Whether the two candidates can't both be true
+fn weakly_linked(a: &Node, b: &Node) -> bool {
+    (a.0 == b.0 && a.1 != b.1) || (a.1 == b.1 && sees(&a.0, &b.0))
+}
+
+// Two candidates where at most one can be true
+fn weak_links(board: &Board, kind: ChainKind, node: &Node) -> Vec<Node> {
+    let (cell, digit) = node;
+    let mut links: Vec<Node> = vec![];
+    if kind == ChainKind::Alternating {
+        for other in board.candidates(cell).iter().filter(|d| *d != digit) {
+            links.push((cell.clone(), *other));
+        }
+    }
+    for peer in peers(cell) {
+        if board.has_candidate(peer, *digit) {
+            links.push((peer.clone(), *digit));
+        }
+    }
+    links
+}
+
+// A chain of candidates alternating between strong and weak links, starting
+// and ending with a strong link, so at least one of the ends must be true.
+// A chain that ends where it started shows that its first candidate must be
+// true, since assuming it false leads back to it being true.
+#[derive(Debug, Clone)]
+pub struct Chain {
+    pub kind: ChainKind,
+    pub nodes: Vec<Node>,
+    // The last node is weakly linked back to the first, which makes every
+    // weak link in the loop strong
+    pub continuous: bool,
+    pub eliminations: Vec<(BoardIndex, u8)>,
+}
+
+impl Chain {
+    pub fn name(&self) -> &'static str {
+        if self.placement().is_some() {
+            return "Discontinuous Nice Loop";
+        }
+        match (self.continuous, self.kind) {
+            (true, _) => "Continuous Nice Loop",
+            (false, ChainKind::X) => "X-Chain",
+            (false, ChainKind::XY) => "XY-Chain",
+            (false, ChainKind::Alternating) => "AIC",
+        }
+    }
+
+    // The first candidate of a chain that returns to it
+    pub fn placement(&self) -> Option<&Node> {
+        let first = &self.nodes[0];
+        (self.nodes.len() > 1 && self.nodes[self.nodes.len() - 1] == *first).then_some(first)
+    }
+
+    // The chain in Eureka notation, like `(4)r1c2=(4)r1c7-(4)r5c7=(4)r5c2`,
+    // where `=` is a strong link and `-` is a weak one
+    pub fn eureka(&self) -> String {
+        let mut notation = String::new();
+        for (n, (cell, digit)) in self.nodes.iter().enumerate() {
+            if n > 0 {
+                notation.push(if n % 2 == 1 { '=' } else { '-' });
+            }
+            notation.push_str(&format!("({}){}", digit, cell.label()));
+        }
+        if self.continuous {
+            let (cell, digit) = &self.nodes[0];
+            notation.push_str(&format!("-({}){}", digit, cell.label()));
+        }
+        notation
+    }
+
+    pub fn to_step(&self, board: &Board) -> Step {
+        let mut cells: Vec<BoardIndex> = vec![];
+        for (cell, _) in &self.nodes {
+            if !cells.contains(cell) {
+                cells.push(cell.clone());
+            }
+        }
+        let mut digits: Vec<u8> = self.nodes.iter().map(|(_, d)| *d).collect();
+        digits.sort();
+        digits.dedup();
+        let diff = match self.placement() {
+            Some((cell, digit)) => board.placement_diff(cell, *digit),
+            None => board.elimination_diff(&self.eliminations),
+        };
+        Step {
+            proof: vec![self.eureka()],
+            nodes: self.nodes.len(),
+            ..Step::new(self.name(), cells, digits, vec![], diff)
+        }
+    }
+}
+
+// Candidates outside of the chain that are weakly linked to both nodes
+fn eliminations_between(board: &Board, chain: &[Node], a: &Node, b: &Node) -> Vec<(BoardIndex, u8)> {
+    let mut cells: Vec<BoardIndex> = [vec![a.0.clone(), b.0.clone()], peers(&a.0).to_vec()].concat();
+    cells.sort();
+    cells.dedup();
+    cells
+        .into_iter()
+        .flat_map(|c| board.candidates(&c).iter().map(move |d| (c.clone(), *d)).collect::<Vec<Node>>())
+        .filter(|n| !chain.contains(n))
+        .filter(|n| weakly_linked(n, a) && weakly_linked(n, b))
+        .collect()
+}
+
+fn chain_eliminations(board: &Board, nodes: &[Node], continuous: bool) -> Vec<(BoardIndex, u8)> {
+    let first = &nodes[0];
+    let last = &nodes[nodes.len() - 1];
+    let mut eliminations = eliminations_between(board, nodes, first, last);
+    if continuous {
+        // The weak links inside the loop, at odd positions
+        for pair in nodes[1..].chunks(2).filter(|p| p.len() == 2) {
+            for elimination in eliminations_between(board, nodes, &pair[0], &pair[1]) {
+                if !eliminations.contains(&elimination) {
+                    eliminations.push(elimination);
+                }
+            }
+        }
+    }
+    eliminations
+}
+
+// Searches outwards from the start, assuming it is false, following strong
+// links to candidates that must then be true and weak links from those to
+// candidates that must then be false. Every chain that ends on a true
+// candidate is passed to `found`, shortest first, including one that ends on
+// the start itself. A loop back to the start through two weak links needs no
+// search of its own, as it's the chain between the start's neighbours.
+fn search_from<F>(board: &Board, kind: ChainKind, start: &Node, max_nodes: usize, mut found: F)
+where
+    F: FnMut(Vec<Node>) -> bool,
+{
+    // (node, is true, index of the previous state)
+    let mut states: Vec<(Node, bool, Option<usize>)> = vec![(start.clone(), false, None)];
+    let mut visited = [[false; 2]; 81 * 9];
+    let slot = |(cell, digit): &Node| cell.value as usize * 9 + *digit as usize - 1;
+    visited[slot(start)][0] = true;
+    let mut queue: VecDeque<(usize, usize)> = VecDeque::new();
+    queue.push_back((0, 1));
+
+    while let Some((state, length)) = queue.pop_front() {
+        if length >= max_nodes {
+            continue;
+        }
+        let (node, truth, _) = states[state].clone();
+        let next = if truth {
+            weak_links(board, kind, &node)
+        } else {
+            strong_links(board, kind, &node)
+        };
+
+        for link in next {
+            if visited[slot(&link)][!truth as usize] {
+                continue;
+            }
+            // Nodes can't repeat within a chain
+            let mut path: Vec<Node> = vec![link.clone()];
+            let mut previous = Some(state);
+            while let Some(p) = previous {
+                path.push(states[p].0.clone());
+                previous = states[p].2;
+            }
+            if path[1..].contains(&link) {
+                if link == *start && !truth {
+                    path.reverse();
+                    if found(path) {
+                        return;
+                    }
+                }
+                continue;
+            }
+            visited[slot(&link)][!truth as usize] = true;
+            states.push((link.clone(), !truth, Some(state)));
+            let added = states.len() - 1;
+            if !truth && path.len() >= 4 {
+                path.reverse();
+                if found(path) {
+                    return;
+                }
+            }
+            queue.push_back((added, length + 1));
+        }
+    }
+}
+
+// Finds the shortest chain of the given kind with eliminations. Ties are
+// broken by the order of the starting candidates, from the top-left cell and
+// lowest digit, so the same board always gives the same chain.
+pub fn find_chain(board: &Board, kind: ChainKind, max_nodes: usize) -> Option<Chain> {
+    let starts: Vec<Node> = board
+        .unsolved_indexes()
+        .into_iter()
+        .flat_map(|c| board.candidates(&c).iter().map(move |d| (c.clone(), *d)).collect::<Vec<Node>>())
+        .collect();
+
+    let mut best: Option<Chain> = None;
+    for start in &starts {
+        let limit = best.as_ref().map_or(max_nodes, |b| b.nodes.len() - 1);
+        search_from(board, kind, start, limit, |nodes| {
+            if best.as_ref().is_some_and(|b| b.nodes.len() <= nodes.len()) {
+                return true;
+            }
+            if nodes[0] == nodes[nodes.len() - 1] {
+                best = Some(Chain { kind, nodes, continuous: false, eliminations: vec![] });
+                return true;
+            }
+            let continuous = weakly_linked(&nodes[0], &nodes[nodes.len() - 1]);
+            let eliminations = chain_eliminations(board, &nodes, continuous);
+            if eliminations.is_empty() {
+                return false;
+            }
+            best = Some(Chain { kind, nodes, continuous, eliminations });
+            true
+        });
+    }
+    best
+}
+
+pub fn find_x_chain(board: &Board) -> Option<Step> {
+    find_chain(board, ChainKind::X, MAX_CHAIN_NODES).map(|c| c.to_step(board))
+}
+
+pub fn find_xy_chain(board: &Board) -> Option<Step> {
+    find_chain(board, ChainKind::XY, MAX_CHAIN_NODES).map(|c| c.to_step(board))
+}
+
+pub fn find_aic(board: &Board) -> Option<Step> {
+    find_chain(board, ChainKind::Alternating, MAX_CHAIN_NODES).map(|c| c.to_step(board))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::techniques::tests::board_with_options;
+
+    fn eliminated(step: &Step) -> Vec<u8> {
+        step.diff.fragments().iter().map(|f| f.index().value).collect()
+    }
+
+    #[test]
+    fn finds_x_chain() {
+        // 5 is only in two cells of rows 1 and 6, linked by column 1
+        let without_5: &[u8] = &[1, 2, 3, 4, 6, 7, 8, 9];
+        let mut without: Vec<(u8, &[u8])> = vec![];
+        for (row, cols) in [(0, [0, 4]), (5, [0, 5])] {
+            for col in (0..9).filter(|c| !cols.contains(c)) {
+                without.push((row * 9 + col, without_5));
+            }
+        }
+        let board = board_with_options(&without);
+        let chain = find_chain(&board, ChainKind::X, MAX_CHAIN_NODES).unwrap();
+        assert_eq!(chain.eureka(), "(5)r1c5=(5)r1c1-(5)r6c1=(5)r6c6");
+        assert_eq!(chain.name(), "X-Chain");
+        let step = find_x_chain(&board).unwrap();
+        assert_eq!(eliminated(&step), vec![14, 23, 31, 40]);
+        assert_eq!(step.nodes, 4);
+    }
+
+    #[test]
+    fn finds_xy_chain() {
+        // Three bivalue cells where r1c1 or r5c5 must be a 1
+        let board = board_with_options(&[(0, &[1, 2]), (4, &[2, 3]), (40, &[1, 3])]);
+        let chain = find_chain(&board, ChainKind::XY, MAX_CHAIN_NODES).unwrap();
+        assert_eq!(chain.eureka(), "(1)r1c1=(2)r1c1-(2)r1c5=(3)r1c5-(3)r5c5=(1)r5c5");
+        assert_eq!(chain.eliminations, vec![(BoardIndex::new(36).unwrap(), 1)]);
+        assert_eq!(find_xy_chain(&board).unwrap().technique, "XY-Chain");
+    }
+
+    #[test]
+    fn respects_length_limit() {
+        let board = board_with_options(&[(0, &[1, 2]), (4, &[2, 3]), (40, &[1, 3])]);
+        assert!(find_chain(&board, ChainKind::XY, 5).is_none());
+    }
+
+    #[test]
+    fn finds_discontinuous_loop() {
+        // Rows 1 and 7 and columns 1 and 5 join r1c1, r1c4, r2c5, r7c5 and
+        // r7c1 in a loop, where r1c1 not being 5 would leave row 1 without a 5
+        let without_5: &[u8] = &[1, 2, 3, 4, 6, 7, 8, 9];
+        let with_5 = [0, 3, 13, 23, 54, 58, 62];
+        let without: Vec<(u8, &[u8])> = (0..81).filter(|i| !with_5.contains(i)).map(|i| (i, without_5)).collect();
+        let board = board_with_options(&without);
+        let start = (BoardIndex::new(0).unwrap(), 5);
+        let mut loops: Vec<Vec<Node>> = vec![];
+        search_from(&board, ChainKind::X, &start, MAX_CHAIN_NODES, |nodes| {
+            if nodes[0] == nodes[nodes.len() - 1] {
+                loops.push(nodes);
+            }
+            false
+        });
+        let chain = Chain { kind: ChainKind::X, nodes: loops[0].clone(), continuous: false, eliminations: vec![] };
+        assert_eq!(chain.eureka(), "(5)r1c1=(5)r1c4-(5)r2c5=(5)r7c5-(5)r7c1=(5)r1c1");
+        assert_eq!(chain.name(), "Discontinuous Nice Loop");
+        assert_eq!(chain.to_step(&board).placements(), vec![start]);
+    }
+
+    #[test]
+    fn writes_continuous_loop_in_eureka_notation() {
+        let node = |i: u8, d: u8| (BoardIndex::new(i).unwrap(), d);
+        let chain = Chain {
+            kind: ChainKind::Alternating,
+            nodes: vec![node(0, 1), node(0, 2), node(4, 2), node(4, 1)],
+            continuous: true,
+            eliminations: vec![],
+        };
+        assert_eq!(chain.eureka(), "(1)r1c1=(2)r1c1-(2)r1c5=(1)r1c5-(1)r1c1");
+        assert_eq!(chain.name(), "Continuous Nice Loop");
+    }
+}
@@ -0,0 +1,186 @@
+use crate::board_index::BoardIndex;
+use crate::board_index::House;
+use crate::techniques::Step;
+
+// Words in technique names that keep their capital in a sentence
+const PROPER_NOUNS: &[&str] = &["Coq"];
+
+// Joins the items like "a, b and c"
+pub(crate) fn list<T: ToString>(items: &[T]) -> String {
+    let items: Vec<String> = items.iter().map(|i| i.to_string()).collect();
+    match items.split_last() {
+        None => String::new(),
+        Some((last, [])) => last.clone(),
+        Some((last, rest)) => format!("{} and {}", rest.join(", "), last),
+    }
+}
+
+pub(crate) fn cell_list(cells: &[BoardIndex]) -> String {
+    list(&cells.iter().map(|c| c.label()).collect::<Vec<String>>())
+}
+
+// The technique name as it would start a sentence, like "Hidden single" for
+// "Hidden Single". Words with more than one capital, like "X-Wing", are left
+// alone.
+pub(crate) fn sentence_case(name: &str) -> String {
+    let words: Vec<String> = name
+        .split(' ')
+        .enumerate()
+        .map(|(n, word)| {
+            let capitals = word.chars().filter(|c| c.is_uppercase()).count();
+            if n == 0 || capitals != 1 || PROPER_NOUNS.contains(&word) || !word.chars().all(char::is_alphabetic) {
+                word.to_string()
+            } else {
+                word.to_lowercase()
+            }
+        })
+        .collect();
+    words.join(" ")
+}
+
+impl Step {
+    // The cells the step solves, with their values
+    pub fn placements(&self) -> Vec<(BoardIndex, u8)> {
+        self.diff
+            .fragments()
+            .iter()
+            .filter_map(|f| f.fragment().placed().map(|value| (f.index().clone(), value)))
+            .collect()
+    }
+
+    // The candidates the step removes from cells it doesn't solve
+    pub fn eliminations(&self) -> Vec<(BoardIndex, u8)> {
+        self.diff
+            .fragments()
+            .iter()
+            .filter(|f| f.fragment().placed().is_none())
+            .flat_map(|f| f.fragment().removed().iter().map(|d| (f.index().clone(), *d)))
+            .collect()
+    }
+
+    // The cells of the pattern in r#c# notation
+    pub fn cell_labels(&self) -> Vec<String> {
+        self.cells.iter().map(|c| c.label()).collect()
+    }
+
+    // What the pattern is, without what follows from it
+    fn pattern(&self) -> String {
+        let digits = list(&self.digits);
+        let cells = cell_list(&self.cells);
+        let houses = |houses: &[House]| list(houses);
+        match self.technique {
+            "Naked Single" => format!("{} can only be {}", cells, digits),
+            "Hidden Single" => format!("{} can only go in {} within {}", digits, cells, houses(&self.houses)),
+            "Pointing" | "Claiming" if self.houses.len() == 2 => format!("{} in {} can only go in {}", digits, self.houses[0], self.houses[1]),
+            "Naked Pair" | "Naked Triple" | "Naked Quad" => {
+                format!("{} in {} only hold {} between them", cells, houses(&self.houses), digits)
+            }
+            "Hidden Pair" | "Hidden Triple" | "Hidden Quad" => {
+                format!("{} can only go in {} within {}", digits, cells, houses(&self.houses))
+            }
+            name if ["X-Wing", "Swordfish", "Jellyfish"].iter().any(|fish| name.ends_with(fish)) => {
+                let (base, cover) = self.houses.split_at(self.houses.len() / 2);
+                let fins = match self.fins.len() {
+                    0 => String::new(),
+                    1 => format!(" or its fin {}", cell_list(&self.fins)),
+                    _ => format!(" or its fins {}", cell_list(&self.fins)),
+                };
+                format!("{} in {} can only go in {}{}", digits, houses(base), houses(cover), fins)
+            }
+            _ if self.houses.is_empty() => format!("{} in {}", digits, cells),
+            _ => format!("{} in {} within {}", digits, cells, houses(&self.houses)),
+        }
+    }
+
+    // What follows from the pattern, like "r1c5 must be 3" or "5 can be
+    // removed from r1c4 and r1c6"
+    fn outcome(&self) -> String {
+        let placements = self.placements();
+        if !placements.is_empty() {
+            let placed: Vec<String> = placements.iter().map(|(i, d)| format!("{} must be {}", i.label(), d)).collect();
+            return list(&placed);
+        }
+        let eliminations = self.eliminations();
+        let mut digits: Vec<u8> = eliminations.iter().map(|(_, d)| *d).collect();
+        digits.sort();
+        digits.dedup();
+        // Digits removed from the same cells are mentioned together
+        let mut groups: Vec<(Vec<u8>, Vec<BoardIndex>)> = vec![];
+        for digit in digits {
+            let cells: Vec<BoardIndex> =
+                eliminations.iter().filter(|(_, d)| *d == digit).map(|(i, _)| i.clone()).collect();
+            match groups.iter_mut().find(|(_, c)| *c == cells) {
+                Some((digits, _)) => digits.push(digit),
+                None => groups.push((vec![digit], cells)),
+            }
+        }
+        let removed: Vec<String> = groups
+            .iter()
+            .map(|(digits, cells)| format!("{} can be removed from {}", list(digits), cell_list(cells)))
+            .collect();
+        removed.join("; ")
+    }
+
+    // The step as a plain-English sentence, like "Hidden single: 7 can only
+    // go in r3c5 within box 2"
+    pub fn explanation(&self) -> String {
+        let mut sentence = format!("{}: {}", sentence_case(self.technique), self.pattern());
+        if !matches!(self.technique, "Naked Single" | "Hidden Single") {
+            sentence.push_str(&format!(", so {}", self.outcome()));
+        }
+        if self.assumes_unique {
+            sentence.push_str(" (assuming the puzzle has a single solution)");
+        }
+        sentence
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+    use crate::techniques::intersections;
+    use crate::techniques::singles;
+    use crate::techniques::tests::board_with_options;
+
+    #[test]
+    fn writes_technique_names_as_sentences() {
+        assert_eq!(sentence_case("Hidden Single"), "Hidden single");
+        assert_eq!(sentence_case("Finned X-Wing"), "Finned X-Wing");
+        assert_eq!(sentence_case("Sue de Coq"), "Sue de Coq");
+        assert_eq!(sentence_case("Unique Rectangle Type 1"), "Unique rectangle type 1");
+        assert_eq!(sentence_case("BUG+1"), "BUG+1");
+    }
+
+    #[test]
+    fn explains_singles() {
+        let mut board = Board::from_line("200080300060070084030500209000105408000000000402706000301007040720040060004010003").unwrap();
+        board.prune_options().unwrap();
+        let step = singles::find_hidden_single(&board).unwrap();
+        assert_eq!(step.explanation(), "Hidden single: 4 can only go in r1c2 within box 1");
+        assert_eq!(step.placements(), vec![(BoardIndex::new(1).unwrap(), 4)]);
+    }
+
+    #[test]
+    fn explains_eliminations() {
+        let without_5: &[u8] = &[1, 2, 3, 4, 6, 7, 8, 9];
+        let without: Vec<(u8, &[u8])> = (2..9).map(|i| (i, without_5)).collect();
+        let board = board_with_options(&without);
+        let step = intersections::find_claiming(&board).unwrap();
+        assert_eq!(step.cell_labels(), vec!["r1c1", "r1c2"]);
+        assert_eq!(
+            step.explanation(),
+            "Claiming: 5 in row 1 can only go in box 1, so 5 can be removed from r2c1, r2c2, r2c3, r3c1, r3c2 and r3c3"
+        );
+        assert!(step.placements().is_empty());
+    }
+
+    #[test]
+    fn falls_back_for_steps_without_the_usual_houses() {
+        // A custom technique may reuse a builtin name without its houses
+        let board = Board::from_line(&".".repeat(81)).unwrap();
+        let index = BoardIndex::new(0).unwrap();
+        let step = Step::new("Pointing", vec![index.clone()], vec![5], vec![], board.elimination_diff(&[(index, 5)]));
+        assert_eq!(step.explanation(), "Pointing: 5 in r1c1, so 5 can be removed from r1c1");
+    }
+}
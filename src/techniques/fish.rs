@@ -0,0 +1,307 @@
+use crate::board::Board;
+use crate::board_index::BoardIndex;
+use crate::board_index::BoardLocation;
+use crate::board_index::House;
+use crate::techniques::combinations;
+use crate::techniques::subsets::positions;
+use crate::techniques::Step;
+
+// N base lines whose candidates for a digit are confined to N cover lines,
+// apart from any fins. Without fins the digit can be removed from the rest of
+// the cover lines. With fins, which must all share a box, it can only be
+// removed from cover cells in that box.
+#[derive(Debug, Clone)]
+pub struct Fish {
+    pub digit: u8,
+    pub base: Vec<House>,
+    pub cover: Vec<House>,
+    // Base candidates outside of the cover lines
+    pub fins: Vec<BoardIndex>,
+    // A finned fish where a base line has only one cell in the cover lines
+    pub sashimi: bool,
+    // Base candidates inside of the cover lines
+    pub cells: Vec<BoardIndex>,
+    pub eliminations: Vec<(BoardIndex, u8)>,
+}
+
+impl Fish {
+    pub fn name(&self) -> &'static str {
+        let finned = !self.fins.is_empty();
+        match (self.base.len(), finned, self.sashimi) {
+            (2, false, _) => "X-Wing",
+            (3, false, _) => "Swordfish",
+            (4, false, _) => "Jellyfish",
+            (2, true, false) => "Finned X-Wing",
+            (3, true, false) => "Finned Swordfish",
+            (4, true, false) => "Finned Jellyfish",
+            (2, true, true) => "Sashimi X-Wing",
+            (3, true, true) => "Sashimi Swordfish",
+            (4, true, true) => "Sashimi Jellyfish",
+            _ => unreachable!("fish have between 2 and 4 base lines"),
+        }
+    }
+
+    pub fn to_step(&self, board: &Board) -> Step {
+        let mut cells = self.cells.clone();
+        cells.sort();
+        let mut fins = self.fins.clone();
+        fins.sort();
+        Step {
+            fins,
+            ..Step::new(
+                self.name(),
+                cells,
+                vec![self.digit],
+                [self.base.clone(), self.cover.clone()].concat(),
+                board.elimination_diff(&self.eliminations),
+            )
+        }
+    }
+}
+
+// The line crossing the index in the other direction from the base lines
+fn cross_line(rows_base: bool, index: &BoardIndex) -> House {
+    if rows_base {
+        House::Col(index.board_col())
+    } else {
+        House::Row(index.board_row())
+    }
+}
+
+fn eliminations(
+    board: &Board,
+    digit: u8,
+    base: &[House],
+    cover: &[House],
+    fin_box: Option<u8>,
+) -> Vec<(BoardIndex, u8)> {
+    cover
+        .iter()
+        .flat_map(|line| line.indexes())
+        .filter(|i| !base.iter().any(|b| b.contains(i)))
+        .filter(|i| fin_box.is_none_or(|b| i.board_box() == b))
+        .filter(|i| board.has_candidate(i, digit))
+        .map(|i| (i, digit))
+        .collect()
+}
+
+// Finds a fish with the given number of base lines, using rows as the base
+// before columns. Only finned and sashimi fish are returned when `finned` is
+// set.
+pub fn find_fish(board: &Board, size: usize, finned: bool) -> Option<Fish> {
+    for digit in 1..=9 {
+        for rows_base in [true, false] {
+            let lines = if rows_base { House::rows() } else { House::cols() };
+            let lines: Vec<(House, Vec<BoardIndex>)> = lines
+                .into_iter()
+                .map(|line| (line, positions(board, &line, digit)))
+                .filter(|(_, cells)| !cells.is_empty())
+                .collect();
+
+            for base_lines in combinations(&lines, size) {
+                let base: Vec<House> = base_lines.iter().map(|(line, _)| *line).collect();
+                let cells: Vec<BoardIndex> = base_lines
+                    .iter()
+                    .flat_map(|(_, cells)| cells.clone())
+                    .collect();
+                let fish = if finned {
+                    find_finned(board, digit, rows_base, &base, &base_lines, &cells)
+                } else {
+                    find_basic(board, digit, rows_base, &base, &cells)
+                };
+                if fish.is_some() {
+                    return fish;
+                }
+            }
+        }
+    }
+    None
+}
+
+fn find_basic(
+    board: &Board,
+    digit: u8,
+    rows_base: bool,
+    base: &[House],
+    cells: &[BoardIndex],
+) -> Option<Fish> {
+    let mut cover: Vec<House> = cells.iter().map(|i| cross_line(rows_base, i)).collect();
+    cover.sort();
+    cover.dedup();
+    if cover.len() != base.len() {
+        return None;
+    }
+    let eliminations = eliminations(board, digit, base, &cover, None);
+    if eliminations.is_empty() {
+        return None;
+    }
+    Some(Fish {
+        digit,
+        base: base.to_vec(),
+        cover,
+        fins: vec![],
+        sashimi: false,
+        cells: cells.to_vec(),
+        eliminations,
+    })
+}
+
+fn find_finned(
+    board: &Board,
+    digit: u8,
+    rows_base: bool,
+    base: &[House],
+    base_lines: &[(House, Vec<BoardIndex>)],
+    cells: &[BoardIndex],
+) -> Option<Fish> {
+    let mut fin_boxes: Vec<u8> = cells.iter().map(|i| i.board_box()).collect();
+    fin_boxes.sort();
+    fin_boxes.dedup();
+
+    for fin_box in fin_boxes {
+        // Cells outside of the fin box must all be covered
+        let mut required: Vec<House> = cells
+            .iter()
+            .filter(|i| i.board_box() != fin_box)
+            .map(|i| cross_line(rows_base, i))
+            .collect();
+        required.sort();
+        required.dedup();
+        if required.len() > base.len() {
+            continue;
+        }
+
+        let mut optional: Vec<House> = cells
+            .iter()
+            .filter(|i| i.board_box() == fin_box)
+            .map(|i| cross_line(rows_base, i))
+            .filter(|line| !required.contains(line))
+            .collect();
+        optional.sort();
+        optional.dedup();
+
+        for extra in combinations(&optional, base.len() - required.len()) {
+            let mut cover = [required.clone(), extra].concat();
+            cover.sort();
+
+            let (covered, fins): (Vec<BoardIndex>, Vec<BoardIndex>) = cells
+                .iter()
+                .cloned()
+                .partition(|i| cover.iter().any(|c| c.contains(i)));
+            if fins.is_empty() {
+                continue;
+            }
+            // Every base line needs a cell in the cover lines
+            let covered_per_line: Vec<usize> = base_lines
+                .iter()
+                .map(|(_, line_cells)| line_cells.iter().filter(|i| covered.contains(i)).count())
+                .collect();
+            if covered_per_line.contains(&0) {
+                continue;
+            }
+
+            let eliminations = eliminations(board, digit, base, &cover, Some(fin_box));
+            if eliminations.is_empty() {
+                continue;
+            }
+            return Some(Fish {
+                digit,
+                base: base.to_vec(),
+                cover,
+                fins,
+                sashimi: covered_per_line.contains(&1),
+                cells: covered,
+                eliminations,
+            });
+        }
+    }
+    None
+}
+
+pub fn find_x_wing(board: &Board) -> Option<Step> {
+    find_fish(board, 2, false).map(|f| f.to_step(board))
+}
+
+pub fn find_swordfish(board: &Board) -> Option<Step> {
+    find_fish(board, 3, false).map(|f| f.to_step(board))
+}
+
+pub fn find_jellyfish(board: &Board) -> Option<Step> {
+    find_fish(board, 4, false).map(|f| f.to_step(board))
+}
+
+pub fn find_finned_x_wing(board: &Board) -> Option<Step> {
+    find_fish(board, 2, true).map(|f| f.to_step(board))
+}
+
+pub fn find_finned_swordfish(board: &Board) -> Option<Step> {
+    find_fish(board, 3, true).map(|f| f.to_step(board))
+}
+
+pub fn find_finned_jellyfish(board: &Board) -> Option<Step> {
+    find_fish(board, 4, true).map(|f| f.to_step(board))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::techniques::tests::board_with_options;
+
+    const WITHOUT_5: &[u8] = &[1, 2, 3, 4, 6, 7, 8, 9];
+
+    // Limits 5 to the given columns of rows 1 and 5
+    fn board_with_5_in(row_1: &[u8], row_5: &[u8]) -> Board {
+        let mut without: Vec<(u8, &[u8])> = vec![];
+        for col in 0..9 {
+            if !row_1.contains(&col) {
+                without.push((9 + col, WITHOUT_5));
+            }
+            if !row_5.contains(&col) {
+                without.push((45 + col, WITHOUT_5));
+            }
+        }
+        board_with_options(&without)
+    }
+
+    fn eliminated(fish: &Fish) -> Vec<u8> {
+        fish.eliminations.iter().map(|(i, _)| i.value).collect()
+    }
+
+    #[test]
+    fn finds_x_wing() {
+        let board = board_with_5_in(&[2, 6], &[2, 6]);
+        let fish = find_fish(&board, 2, false).unwrap();
+        assert_eq!(fish.name(), "X-Wing");
+        assert_eq!(fish.base, vec![House::Row(1), House::Row(5)]);
+        assert_eq!(fish.cover, vec![House::Col(2), House::Col(6)]);
+        assert_eq!(eliminated(&fish), vec![2, 20, 29, 38, 56, 65, 74, 6, 24, 33, 42, 60, 69, 78]);
+        assert!(find_fish(&board, 2, true).is_none());
+    }
+
+    #[test]
+    fn finds_finned_x_wing() {
+        let board = board_with_5_in(&[2, 6], &[2, 6, 7]);
+        assert!(find_fish(&board, 2, false).is_none());
+        let fish = find_fish(&board, 2, true).unwrap();
+        assert_eq!(fish.name(), "Finned X-Wing");
+        assert_eq!(fish.cover, vec![House::Col(2), House::Col(6)]);
+        assert_eq!(fish.fins, vec![BoardIndex::new(52).unwrap()]);
+        assert_eq!(eliminated(&fish), vec![33, 42]);
+        let step = fish.to_step(&board);
+        assert_eq!(step.fins, fish.fins);
+        assert_eq!(
+            step.explanation(),
+            "Finned X-Wing: 5 in row 2 and row 6 can only go in column 3 and column 7 or its fin r6c8, so 5 can be removed from r4c7 and r5c7"
+        );
+    }
+
+    #[test]
+    fn finds_sashimi_x_wing() {
+        let board = board_with_5_in(&[2, 6], &[2, 7, 8]);
+        let fish = find_fish(&board, 2, true).unwrap();
+        assert_eq!(fish.name(), "Sashimi X-Wing");
+        assert_eq!(fish.cells.len(), 3);
+        assert_eq!(fish.fins.len(), 2);
+        assert_eq!(eliminated(&fish), vec![33, 42]);
+    }
+}
@@ -0,0 +1,324 @@
+use crate::board::Board;
+use crate::board::BoardFragment;
+use crate::board_index::BoardIndex;
+use crate::board_index::House;
+use crate::diff::Diff;
+use crate::diff::PatchDiff;
+use crate::techniques::singles;
+use crate::techniques::subsets::positions;
+use crate::techniques::Step;
+
+// The most singles a trial follows after its assumption
+pub const MAX_TRIAL_STEPS: usize = 40;
+
+// The changes that follow from assuming a candidate is true. The diff has
+// been applied to the board the trial ran on.
+struct Trial {
+    diff: Diff<BoardFragment>,
+    trace: Vec<String>,
+    contradiction: bool,
+    // The cells the trial solved and the candidates it removed from unsolved
+    // cells, filled in once the trial is over
+    placements: Vec<(BoardIndex, u8)>,
+    eliminations: Vec<(BoardIndex, u8)>,
+}
+
+impl Trial {
+    fn new(diff: Diff<BoardFragment>, trace: Vec<String>, contradiction: bool) -> Trial {
+        Trial { diff, trace, contradiction, placements: vec![], eliminations: vec![] }
+    }
+
+    // Records what the trial changed, comparing the board before it with the
+    // board after it
+    fn record_consequences(&mut self, before: &Board, after: &Board) {
+        for index in before.unsolved_indexes() {
+            if let Some(value) = after.get_cell(&index).value {
+                self.placements.push((index.clone(), value));
+            }
+            for digit in before.candidates(&index) {
+                if !after.get_cell(&index).options.contains(digit) {
+                    self.eliminations.push((index.clone(), *digit));
+                }
+            }
+        }
+    }
+}
+
+// Why the board can't be solved any more, if it can't: an unsolved cell
+// without candidates, or a digit without a place in a house
+fn find_contradiction(board: &Board, digits: &[u8]) -> Option<String> {
+    if let Some(index) = board.unsolved_indexes().into_iter().find(|i| board.candidates(i).is_empty()) {
+        return Some(format!("{} has no candidates left", index.label()));
+    }
+    for house in House::all() {
+        for digit in digits {
+            let placed = house.indexes().iter().any(|i| board.get_cell(i).value == Some(*digit));
+            if !placed && positions(board, &house, *digit).is_empty() {
+                return Some(format!("{} has no place left in {}", digit, house));
+            }
+        }
+    }
+    None
+}
+
+// The only cell left for the digit in some house, with that house
+fn hidden_single_of(board: &Board, digit: u8) -> Option<(BoardIndex, House)> {
+    House::all().into_iter().find_map(|house| {
+        let cells = positions(board, &house, digit);
+        if cells.len() == 1 {
+            Some((cells[0].clone(), house))
+        } else {
+            None
+        }
+    })
+}
+
+// Places the digit and follows singles from it until nothing more follows, a
+// contradiction is found or the trial gets too long. With `only_digit` just
+// the hidden singles of that digit are followed, as in Nishio.
+fn assume(board: &mut Board, index: &BoardIndex, digit: u8, only_digit: bool) -> Trial {
+    let all_digits: Vec<u8> = (1..=9).collect();
+    let digits = if only_digit { vec![digit] } else { all_digits };
+
+    let mut diff = board.placement_diff(index, digit);
+    board.apply_diff(&diff);
+    let mut trace = vec![format!("{}={}", index.label(), digit)];
+    let mut steps = 0;
+    loop {
+        if let Some(reason) = find_contradiction(board, &digits) {
+            trace.push(reason);
+            return Trial::new(diff, trace, true);
+        }
+        if steps == MAX_TRIAL_STEPS {
+            break;
+        }
+        let (next, description) = if only_digit {
+            match hidden_single_of(board, digit) {
+                Some((cell, house)) => (
+                    board.placement_diff(&cell, digit),
+                    format!("{}={} (only place in {})", cell.label(), digit, house),
+                ),
+                None => break,
+            }
+        } else {
+            match singles::find_naked_single(board).or_else(|| singles::find_hidden_single(board)) {
+                Some(step) => (
+                    step.diff,
+                    format!("{}={} ({})", step.cells[0].label(), step.digits[0], step.technique.to_lowercase()),
+                ),
+                None => break,
+            }
+        };
+        board.apply_diff(&next);
+        trace.push(description);
+        diff.extend(next);
+        steps += 1;
+    }
+    Trial::new(diff, trace, false)
+}
+
+// Assumes each branch in turn on a scratch copy of the board,
+// reverting each trial before the next
+fn run_trials(board: &Board, branches: &[(BoardIndex, u8)], only_digit: bool) -> Vec<Trial> {
+    let mut scratch = board.clone();
+    branches
+        .iter()
+        .map(|(index, digit)| {
+            let mut trial = assume(&mut scratch, index, *digit, only_digit);
+            trial.record_consequences(board, &scratch);
+            scratch.revert_diff(&trial.diff);
+            trial
+        })
+        .collect()
+}
+
+// Assumes a candidate is true and follows only where the digit is forced to
+// go. If the digit then has no place left in some house, the candidate is
+// false.
+pub fn find_nishio(board: &Board) -> Option<Step> {
+    for index in board.unsolved_indexes() {
+        for digit in board.candidates(&index) {
+            let branch = [(index.clone(), *digit)];
+            let trial = run_trials(board, &branch, true).remove(0);
+            if !trial.contradiction {
+                continue;
+            }
+            return Some(Step {
+                proof: vec![
+                    trial.trace.join(" → "),
+                    format!("so {} can't be {}", index.label(), digit),
+                ],
+                nodes: trial.trace.len(),
+                ..Step::new(
+                    "Nishio",
+                    vec![index.clone()],
+                    vec![*digit],
+                    vec![],
+                    board.elimination_diff(&branch),
+                )
+            });
+        }
+    }
+    None
+}
+
+// One of the branches must be true. Branches that lead to a contradiction
+// can't be, and whatever follows from every other branch must be true.
+fn forcing_step(
+    board: &Board,
+    technique: &'static str,
+    branches: &[(BoardIndex, u8)],
+    houses: Vec<House>,
+) -> Option<Step> {
+    let results = run_trials(board, branches, false);
+    let possible: Vec<&Trial> = results.iter().filter(|trial| !trial.contradiction).collect();
+    let first = possible.first()?;
+
+    let mut proof: Vec<String> = results.iter().map(|trial| trial.trace.join(" → ")).collect();
+    let placement = first
+        .placements
+        .iter()
+        .find(|p| possible.iter().all(|trial| trial.placements.contains(p)));
+    let diff = match placement {
+        Some((index, value)) => {
+            proof.push(format!("so {}={}", index.label(), value));
+            board.placement_diff(index, *value)
+        }
+        None => {
+            let eliminations: Vec<(BoardIndex, u8)> = first
+                .eliminations
+                .iter()
+                .filter(|e| possible.iter().all(|trial| trial.eliminations.contains(e)))
+                .cloned()
+                .collect();
+            if eliminations.is_empty() {
+                return None;
+            }
+            let removed: Vec<String> = eliminations.iter().map(|(i, d)| format!("{}<>{}", i.label(), d)).collect();
+            proof.push(format!("so {}", removed.join(", ")));
+            board.elimination_diff(&eliminations)
+        }
+    };
+
+    let mut cells: Vec<BoardIndex> = branches.iter().map(|(i, _)| i.clone()).collect();
+    cells.dedup();
+    let mut digits: Vec<u8> = branches.iter().map(|(_, d)| *d).collect();
+    digits.sort();
+    digits.dedup();
+    Some(Step {
+        proof,
+        nodes: results.iter().map(|trial| trial.trace.len()).sum(),
+        ..Step::new(technique, cells, digits, houses, diff)
+    })
+}
+
+// A cell with two or three candidates where every candidate leads to the
+// same placement or elimination
+pub fn find_cell_forcing_chain(board: &Board) -> Option<Step> {
+    for index in board.unsolved_indexes() {
+        let candidates = board.candidates(&index);
+        if !(2..=3).contains(&candidates.len()) {
+            continue;
+        }
+        let branches: Vec<(BoardIndex, u8)> = candidates.iter().map(|d| (index.clone(), *d)).collect();
+        if let Some(step) = forcing_step(board, "Cell Forcing Chain", &branches, vec![]) {
+            return Some(step);
+        }
+    }
+    None
+}
+
+// A digit with two or three places in a house where every place leads to
+// the same placement or elimination
+pub fn find_region_forcing_chain(board: &Board) -> Option<Step> {
+    for house in House::all() {
+        for digit in 1..=9 {
+            let cells = positions(board, &house, digit);
+            if !(2..=3).contains(&cells.len()) {
+                continue;
+            }
+            let branches: Vec<(BoardIndex, u8)> = cells.into_iter().map(|i| (i, digit)).collect();
+            if let Some(step) = forcing_step(board, "Region Forcing Chain", &branches, vec![house]) {
+                return Some(step);
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::techniques::tests::board_with_options;
+
+    fn index(value: u8) -> BoardIndex {
+        BoardIndex::new(value).unwrap()
+    }
+
+    #[test]
+    fn trials_are_reverted_exactly() {
+        let mut board = Board::from_line(".4.6.2.31.....16.96..54.827..276..8.5.6....74.87..5.6216..8..5.82...7.9.7....62..").unwrap();
+        board.prune_options().unwrap();
+        let original = board.clone();
+        let trial = assume(&mut board, &index(0), 9, false);
+        assert!(!trial.contradiction);
+        assert!(trial.trace.len() > 1);
+        board.revert_diff(&trial.diff);
+        for (a, b) in original.cells.iter().zip(board.cells.iter()) {
+            assert_eq!(a.value, b.value);
+            assert_eq!(a.options, b.options);
+        }
+    }
+
+    #[test]
+    fn finds_nishio() {
+        // 5 can only go in columns 2 and 5 of rows 1 and 9. Assuming r2c2 is 5
+        // forces the 5 of row 1 into r1c5, which leaves none for row 9.
+        let without_5: &[u8] = &[1, 2, 3, 4, 6, 7, 8, 9];
+        let without: Vec<(u8, &[u8])> = [0, 72]
+            .iter()
+            .flat_map(|row| [0, 2, 3, 5, 6, 7, 8].map(|col| (row + col, without_5)))
+            .collect();
+        let board = board_with_options(&without);
+        let step = find_nishio(&board).unwrap();
+        assert_eq!(step.cells, vec![index(10)]);
+        assert_eq!(step.digits, vec![5]);
+        assert_eq!(step.proof, vec![
+            "r2c2=5 → r1c5=5 (only place in row 1) → 5 has no place left in row 9".to_string(),
+            "so r2c2 can't be 5".to_string(),
+        ]);
+    }
+
+    #[test]
+    fn finds_cell_forcing_chain() {
+        // Whether r1c1 is 1 or 2, r1c2 and r1c3 take the other digit and 3,
+        // so 3 can't be anywhere else in row 1
+        let board = board_with_options(&[(0, &[1, 2]), (1, &[1, 3]), (2, &[2, 3])]);
+        let step = find_cell_forcing_chain(&board).unwrap();
+        assert_eq!(step.technique, "Cell Forcing Chain");
+        assert_eq!(step.cells, vec![index(0)]);
+        assert_eq!(step.proof.len(), 3);
+        assert!(step.proof[0].starts_with("r1c1=1 → r1c2=3"));
+
+        let mut after = board.clone();
+        after.apply_diff(&step.diff);
+        assert!(!after.has_candidate(&index(3), 3));
+        assert!(!after.has_candidate(&index(9), 3));
+    }
+
+    #[test]
+    fn finds_region_forcing_chain() {
+        // 7 can only go in r1c1 or r1c2, which both remove 7 from the rest of
+        // box 1
+        let without_7: &[u8] = &[1, 2, 3, 4, 5, 6, 8, 9];
+        let without: Vec<(u8, &[u8])> = (2..9).map(|i| (i, without_7)).collect();
+        let board = board_with_options(&without);
+        let step = find_region_forcing_chain(&board).unwrap();
+        assert_eq!(step.houses, vec![House::Row(0)]);
+        let mut after = board.clone();
+        after.apply_diff(&step.diff);
+        for i in [9, 10, 11, 18, 19, 20] {
+            assert!(!after.has_candidate(&index(i), 7));
+        }
+    }
+}
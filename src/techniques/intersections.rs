@@ -0,0 +1,287 @@
+use crate::board::Board;
+use crate::board_index::BoardIndex;
+use crate::board_index::BoardLocation;
+use crate::board_index::House;
+use crate::techniques::bit_digits;
+use crate::techniques::combinations;
+use crate::techniques::subsets::positions;
+use crate::techniques::Step;
+
+// The row or column shared by every index, if there is one
+fn shared_line(indexes: &[BoardIndex]) -> Option<House> {
+    let first = indexes.first()?;
+    if indexes.iter().all(|i| i.board_row() == first.board_row()) {
+        return Some(House::Row(first.board_row()));
+    }
+    if indexes.iter().all(|i| i.board_col() == first.board_col()) {
+        return Some(House::Col(first.board_col()));
+    }
+    None
+}
+
+// Removes the value from the cells of `target` that aren't in `source`
+fn eliminate_outside(board: &Board, source: &House, target: &House, value: u8) -> Vec<(BoardIndex, u8)> {
+    target
+        .indexes()
+        .into_iter()
+        .filter(|i| !source.contains(i) && board.has_candidate(i, value))
+        .map(|i| (i, value))
+        .collect()
+}
+
+// A digit whose candidates within a box all lie on one row or column. The
+// digit must go in that box's part of the line, so it can be removed from the
+// rest of the line.
+pub fn find_pointing(board: &Board) -> Option<Step> {
+    pointing_steps(board).next()
+}
+
+// Every pointing step on the board, in the order `find_pointing` looks
+pub fn pointing_steps(board: &Board) -> impl Iterator<Item = Step> + '_ {
+    House::boxes()
+        .into_iter()
+        .flat_map(move |board_box| (1..=9).filter_map(move |value| pointing(board, board_box, value)))
+}
+
+fn pointing(board: &Board, board_box: House, value: u8) -> Option<Step> {
+    let cells = positions(board, &board_box, value);
+    if cells.len() < 2 {
+        return None;
+    }
+    let line = shared_line(&cells)?;
+    let eliminations = eliminate_outside(board, &board_box, &line, value);
+    if eliminations.is_empty() {
+        return None;
+    }
+    Some(Step::new(
+        "Pointing",
+        cells,
+        vec![value],
+        vec![board_box, line],
+        board.elimination_diff(&eliminations),
+    ))
+}
+
+// A digit whose candidates within a row or column all lie in one box. The
+// digit must go in that part of the box, so it can be removed from the rest
+// of the box.
+pub fn find_claiming(board: &Board) -> Option<Step> {
+    claiming_steps(board).next()
+}
+
+// Every claiming step on the board, in the order `find_claiming` looks
+pub fn claiming_steps(board: &Board) -> impl Iterator<Item = Step> + '_ {
+    [House::rows(), House::cols()]
+        .concat()
+        .into_iter()
+        .flat_map(move |line| (1..=9).filter_map(move |value| claiming(board, line, value)))
+}
+
+fn claiming(board: &Board, line: House, value: u8) -> Option<Step> {
+    let cells = positions(board, &line, value);
+    if cells.len() < 2 {
+        return None;
+    }
+    let board_box = cells[0].board_box();
+    if cells.iter().any(|i| i.board_box() != board_box) {
+        return None;
+    }
+    let board_box = House::Box(board_box);
+    let eliminations = eliminate_outside(board, &line, &board_box, value);
+    if eliminations.is_empty() {
+        return None;
+    }
+    Some(Step::new(
+        "Claiming",
+        cells,
+        vec![value],
+        vec![line, board_box],
+        board.elimination_diff(&eliminations),
+    ))
+}
+
+// The candidates of the cells as bits, with bit n for the digit n
+fn candidate_bits(board: &Board, cells: &[BoardIndex]) -> u16 {
+    cells
+        .iter()
+        .flat_map(|i| board.candidates(i).iter())
+        .fold(0, |bits, d| bits | 1 << d)
+}
+
+// Removes the digits from the unsolved cells of the house that aren't in the
+// pattern
+fn eliminate_digits(board: &Board, house: &House, pattern: &[BoardIndex], digits: u16) -> Vec<(BoardIndex, u8)> {
+    house
+        .indexes()
+        .into_iter()
+        .filter(|i| !pattern.contains(i))
+        .flat_map(|i| bit_digits(digits).into_iter().map(move |d| (i.clone(), d)))
+        .filter(|(i, d)| board.has_candidate(i, *d))
+        .collect()
+}
+
+// Every group of up to four of the cells, with their candidates as bits
+fn groups(board: &Board, cells: &[BoardIndex]) -> Vec<(Vec<BoardIndex>, u16)> {
+    (1..=cells.len().min(4))
+        .flat_map(|size| combinations(cells, size))
+        .map(|group| {
+            let digits = candidate_bits(board, &group);
+            (group, digits)
+        })
+        .collect()
+}
+
+// Two or three cells where a box and a line cross, with at least two more
+// candidates than cells, plus cells from the rest of the line and the rest of
+// the box that don't share any candidates with each other. When the cells
+// together have as many candidates as there are cells, each candidate is
+// locked into the pattern: the line's digits, and the crossing's digits the
+// box cells don't have, can be removed from the rest of the line, and the
+// same for the box. The line and box cells may have digits the crossing
+// doesn't, which covers the extended forms.
+pub fn find_sue_de_coq(board: &Board) -> Option<Step> {
+    for board_box in House::boxes() {
+        let first = &board_box.indexes()[0];
+        let lines = [
+            (0..3).map(|n| House::Row(first.board_row() + n)).collect::<Vec<House>>(),
+            (0..3).map(|n| House::Col(first.board_col() + n)).collect(),
+        ].concat();
+        for line in lines {
+            if let Some(step) = find_sue_de_coq_in(board, board_box, line) {
+                return Some(step);
+            }
+        }
+    }
+    None
+}
+
+fn find_sue_de_coq_in(board: &Board, board_box: House, line: House) -> Option<Step> {
+    let mut unsolved: Vec<BoardIndex> = [board_box.indexes(), line.indexes()]
+        .concat()
+        .into_iter()
+        .filter(|i| !board.candidates(i).is_empty())
+        .collect();
+    unsolved.sort();
+    unsolved.dedup();
+    let crossing: Vec<BoardIndex> = unsolved.iter().filter(|i| board_box.contains(i) && line.contains(i)).cloned().collect();
+    let line_rest: Vec<BoardIndex> = unsolved.iter().filter(|i| !board_box.contains(i)).cloned().collect();
+    let box_rest: Vec<BoardIndex> = unsolved.iter().filter(|i| !line.contains(i)).cloned().collect();
+    let line_groups = groups(board, &line_rest);
+    let box_groups = groups(board, &box_rest);
+
+    for size in 2..=crossing.len() {
+        for core in combinations(&crossing, size) {
+            let core_digits = candidate_bits(board, &core);
+            if (core_digits.count_ones() as usize) < size + 2 {
+                continue;
+            }
+            for (line_cells, line_digits) in line_groups.iter().filter(|(_, d)| d & core_digits != 0) {
+                for (box_cells, box_digits) in box_groups.iter().filter(|(_, d)| d & core_digits != 0) {
+                    let digits = core_digits | line_digits | box_digits;
+                    let cells = size + line_cells.len() + box_cells.len();
+                    if box_digits & line_digits != 0 || digits.count_ones() as usize != cells {
+                        continue;
+                    }
+                    let pattern = [core.clone(), line_cells.clone(), box_cells.clone()].concat();
+                    let mut eliminations =
+                        eliminate_digits(board, &line, &pattern, line_digits | (core_digits & !box_digits));
+                    eliminations.extend(
+                        eliminate_digits(board, &board_box, &pattern, box_digits | (core_digits & !line_digits))
+                    );
+                    if eliminations.is_empty() {
+                        continue;
+                    }
+                    return Some(Step::new(
+                        "Sue de Coq",
+                        pattern,
+                        bit_digits(digits),
+                        vec![board_box, line],
+                        board.elimination_diff(&eliminations),
+                    ));
+                }
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::PatchDiff;
+    use crate::techniques::tests::board_with_options;
+
+    const WITHOUT_5: &[u8] = &[1, 2, 3, 4, 6, 7, 8, 9];
+
+    #[test]
+    fn finds_pointing_pair() {
+        // 5 can only go in the top row of box 0
+        let without: Vec<(u8, &[u8])> = [2, 9, 10, 11, 18, 19, 20]
+            .iter()
+            .map(|i| (*i, WITHOUT_5))
+            .collect();
+        let board = board_with_options(&without);
+        let step = find_pointing(&board).unwrap();
+        assert_eq!(step.houses, vec![House::Box(0), House::Row(0)]);
+        assert_eq!(step.digits, vec![5]);
+
+        let mut after = board.clone();
+        after.apply_diff(&step.diff);
+        for i in 3..9 {
+            assert!(!after.has_candidate(&BoardIndex::new(i).unwrap(), 5));
+        }
+        assert!(after.has_candidate(&BoardIndex::new(0).unwrap(), 5));
+        assert!(after.has_candidate(&BoardIndex::new(27).unwrap(), 5));
+    }
+
+    #[test]
+    fn finds_claiming_pair() {
+        // 5 can only go in box 0 within row 0
+        let without: Vec<(u8, &[u8])> = (2..9).map(|i| (i, WITHOUT_5)).collect();
+        let board = board_with_options(&without);
+        assert!(find_pointing(&board).is_none());
+        let step = find_claiming(&board).unwrap();
+        assert_eq!(step.houses, vec![House::Row(0), House::Box(0)]);
+
+        let mut after = board.clone();
+        after.apply_diff(&step.diff);
+        for i in [9, 10, 11, 18, 19, 20] {
+            assert!(!after.has_candidate(&BoardIndex::new(i).unwrap(), 5));
+        }
+        assert!(after.has_candidate(&BoardIndex::new(1).unwrap(), 5));
+    }
+
+    #[test]
+    fn finds_sue_de_coq() {
+        // r1c1 and r1c2 have {1, 2, 3, 4}, r1c5 has {1, 2} and r2c1 has {3, 4},
+        // so 1 and 2 are locked into row 1 and 3 and 4 into box 1
+        let board = board_with_options(&[(0, &[1, 2, 3, 4]), (1, &[1, 2, 3, 4]), (4, &[1, 2]), (9, &[3, 4])]);
+        let step = find_sue_de_coq(&board).unwrap();
+        assert_eq!(step.houses, vec![House::Box(0), House::Row(0)]);
+        assert_eq!(step.digits, vec![1, 2, 3, 4]);
+
+        let mut after = board.clone();
+        after.apply_diff(&step.diff);
+        assert_eq!(after.cells[2].options, vec![5, 6, 7, 8, 9]);
+        assert_eq!(after.cells[8].options, vec![3, 4, 5, 6, 7, 8, 9]);
+        assert_eq!(after.cells[20].options, vec![1, 2, 5, 6, 7, 8, 9]);
+        assert_eq!(after.cells[27].options, vec![1, 2, 3, 4, 5, 6, 7, 8, 9]);
+    }
+
+    #[test]
+    fn finds_extended_sue_de_coq() {
+        // As above, but r1c5 also has a 5 that the crossing doesn't
+        let board = board_with_options(&[
+            (0, &[1, 2, 3, 4]),
+            (1, &[1, 2, 3, 4]),
+            (4, &[1, 5]),
+            (5, &[2, 5]),
+            (9, &[3, 4]),
+        ]);
+        let step = find_sue_de_coq(&board).unwrap();
+        assert_eq!(step.digits, vec![1, 2, 3, 4, 5]);
+        let mut after = board.clone();
+        after.apply_diff(&step.diff);
+        assert_eq!(after.cells[8].options, vec![3, 4, 6, 7, 8, 9]);
+    }
+}
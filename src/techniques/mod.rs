@@ -0,0 +1,284 @@
+use std::sync::OnceLock;
+
+use crate::board::Board;
+use crate::board::BoardFragment;
+use crate::board_index::BoardIndex;
+use crate::board_index::BoardLocation;
+use crate::board_index::House;
+use crate::diff::Diff;
+
+pub mod als;
+pub mod chains;
+pub mod explain;
+pub mod fish;
+pub mod forcing;
+pub mod intersections;
+pub mod single_digit;
+pub mod singles;
+pub mod subsets;
+pub mod templates;
+pub mod uniqueness;
+pub mod wings;
+
+// A single logical deduction. The diff holds the placements or eliminations
+// the deduction allows, and hasn't been applied to the board it was found on.
+#[derive(Debug, Clone)]
+pub struct Step {
+    pub technique: &'static str,
+    // The cells that make up the pattern
+    pub cells: Vec<BoardIndex>,
+    // Cells that spoil the pattern but keep the deduction valid, like the
+    // fins of a finned fish
+    pub fins: Vec<BoardIndex>,
+    pub digits: Vec<u8>,
+    pub houses: Vec<House>,
+    pub diff: Diff<BoardFragment>,
+    // The deduction is only valid if the puzzle has a single solution
+    pub assumes_unique: bool,
+    // Lines explaining how the deduction was reached, for techniques that
+    // can't be described by the cells, digits and houses alone
+    pub proof: Vec<String>,
+    // How many candidates the chains of the proof go through, which makes
+    // longer chains harder to rate
+    pub nodes: usize,
+}
+
+impl Step {
+    // A step that holds for any puzzle and needs no proof. Set
+    // `assumes_unique`, `proof` or `nodes` on the result for steps that do.
+    pub fn new(
+        technique: &'static str,
+        cells: Vec<BoardIndex>,
+        digits: Vec<u8>,
+        houses: Vec<House>,
+        diff: Diff<BoardFragment>,
+    ) -> Step {
+        Step { technique, cells, fins: vec![], digits, houses, diff, assumes_unique: false, proof: vec![], nodes: 0 }
+    }
+}
+
+// A way of finding steps. Implement this to add a technique to a `Solver`.
+pub trait Technique {
+    // The name the technique is configured by. The steps it finds may use
+    // more specific names, like a Sashimi X-Wing from the Finned X-Wing
+    // technique.
+    fn name(&self) -> &'static str;
+
+    // The next deduction this technique allows on the board, without
+    // changing the board
+    fn find_step(&self, board: &Board) -> Option<Step>;
+
+    // Whether the technique is only valid when the puzzle has a single
+    // solution
+    fn assumes_unique(&self) -> bool {
+        false
+    }
+}
+
+// One of the techniques in this module
+#[derive(Debug, Clone, Copy)]
+pub struct Builtin {
+    name: &'static str,
+    find: fn(&Board) -> Option<Step>,
+}
+
+impl Builtin {
+    pub const fn new(name: &'static str, find: fn(&Board) -> Option<Step>) -> Builtin {
+        Builtin { name, find }
+    }
+}
+
+impl Technique for Builtin {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn find_step(&self, board: &Board) -> Option<Step> {
+        (self.find)(board)
+    }
+
+    fn assumes_unique(&self) -> bool {
+        UNIQUENESS_TECHNIQUES.iter().any(|t| t.name == self.name)
+    }
+}
+
+// Whether the two technique names are the same, ignoring case, spaces and
+// punctuation, so `x-wing`, `XWing` and `X Wing` all name the X-Wing
+pub(crate) fn same_name(a: &str, b: &str) -> bool {
+    let key = |name: &str| -> String {
+        name.chars().filter(|c| c.is_alphanumeric()).collect::<String>().to_lowercase()
+    };
+    key(a) == key(b)
+}
+
+// Finds a technique in this module by name
+pub fn builtin(name: &str) -> Option<Builtin> {
+    TECHNIQUES
+        .iter()
+        .chain(UNIQUENESS_TECHNIQUES)
+        .find(|t| same_name(t.name, name))
+        .copied()
+}
+
+// Techniques from the cheapest to the most expensive
+pub const TECHNIQUES: &[Builtin] = &[
+    Builtin::new("Naked Single", singles::find_naked_single),
+    Builtin::new("Hidden Single", singles::find_hidden_single),
+    Builtin::new("Pointing", intersections::find_pointing),
+    Builtin::new("Claiming", intersections::find_claiming),
+    Builtin::new("Naked Pair", subsets::find_naked_pair),
+    Builtin::new("X-Wing", fish::find_x_wing),
+    Builtin::new("Hidden Pair", subsets::find_hidden_pair),
+    Builtin::new("Naked Triple", subsets::find_naked_triple),
+    Builtin::new("Swordfish", fish::find_swordfish),
+    Builtin::new("Hidden Triple", subsets::find_hidden_triple),
+    Builtin::new("Skyscraper", single_digit::find_skyscraper),
+    Builtin::new("2-String Kite", single_digit::find_two_string_kite),
+    Builtin::new("Turbot Fish", single_digit::find_turbot_fish),
+    Builtin::new("Empty Rectangle", single_digit::find_empty_rectangle),
+    Builtin::new("XY-Wing", wings::find_xy_wing),
+    Builtin::new("XYZ-Wing", wings::find_xyz_wing),
+    Builtin::new("W-Wing", wings::find_w_wing),
+    Builtin::new("Naked Quad", subsets::find_naked_quad),
+    Builtin::new("Jellyfish", fish::find_jellyfish),
+    Builtin::new("Hidden Quad", subsets::find_hidden_quad),
+    Builtin::new("Color Trap", single_digit::find_color_trap),
+    Builtin::new("Color Wrap", single_digit::find_color_wrap),
+    Builtin::new("Finned X-Wing", fish::find_finned_x_wing),
+    Builtin::new("Finned Swordfish", fish::find_finned_swordfish),
+    Builtin::new("Finned Jellyfish", fish::find_finned_jellyfish),
+    Builtin::new("WXYZ-Wing", wings::find_wxyz_wing),
+    Builtin::new("Sue de Coq", intersections::find_sue_de_coq),
+    Builtin::new("X-Chain", chains::find_x_chain),
+    Builtin::new("XY-Chain", chains::find_xy_chain),
+    Builtin::new("AIC", chains::find_aic),
+    Builtin::new("ALS-XZ", als::find_als_xz),
+    Builtin::new("ALS-XY-Wing", als::find_als_xy_wing),
+    Builtin::new("Death Blossom", als::find_death_blossom),
+    Builtin::new("Template", templates::find_template),
+    Builtin::new("Nishio", forcing::find_nishio),
+    Builtin::new("Cell Forcing Chain", forcing::find_cell_forcing_chain),
+    Builtin::new("Region Forcing Chain", forcing::find_region_forcing_chain),
+];
+
+// Techniques that rely on the puzzle having a single solution. They give
+// wrong answers on puzzles with several solutions, so they are only used
+// when asked for.
+pub const UNIQUENESS_TECHNIQUES: &[Builtin] = &[
+    Builtin::new("Unique Rectangle Type 1", uniqueness::find_unique_rectangle_type_1),
+    Builtin::new("Unique Rectangle Type 2", uniqueness::find_unique_rectangle_type_2),
+    Builtin::new("Unique Rectangle Type 3", uniqueness::find_unique_rectangle_type_3),
+    Builtin::new("Unique Rectangle Type 4", uniqueness::find_unique_rectangle_type_4),
+    Builtin::new("Unique Rectangle Type 5", uniqueness::find_unique_rectangle_type_5),
+    Builtin::new("Unique Rectangle Type 6", uniqueness::find_unique_rectangle_type_6),
+    Builtin::new("Hidden Unique Rectangle", uniqueness::find_hidden_unique_rectangle),
+    Builtin::new("BUG+1", uniqueness::find_bug_plus_one),
+];
+
+// The entangled indexes of the index, worked out once for the whole board
+pub(crate) fn peers(index: &BoardIndex) -> &'static [BoardIndex] {
+    static PEERS: OnceLock<Vec<Vec<BoardIndex>>> = OnceLock::new();
+    let peers = PEERS.get_or_init(|| {
+        (0..81)
+            .map(|i| BoardIndex::new(i).unwrap().entangled_indexes())
+            .collect()
+    });
+    &peers[index.value as usize]
+}
+
+// Whether the two cells share a row, column or box
+pub(crate) fn sees(a: &BoardIndex, b: &BoardIndex) -> bool {
+    peers(a).binary_search(b).is_ok()
+}
+
+// A set of cells as bits, with bit n for the cell at index n
+pub(crate) type CellMask = u128;
+
+// A set of digits as bits, with bit n for the digit n, as a list
+pub(crate) fn bit_digits(bits: u16) -> Vec<u8> {
+    (1..=9).filter(|d| bits & 1 << d != 0).collect()
+}
+
+// Every way of choosing `size` of the items, keeping their order
+pub(crate) fn combinations<T: Clone>(items: &[T], size: usize) -> Vec<Vec<T>> {
+    if size == 0 {
+        return vec![vec![]];
+    }
+    if items.len() < size {
+        return vec![];
+    }
+    let mut result = vec![];
+    for (i, item) in items.iter().enumerate() {
+        for mut rest in combinations(&items[i + 1..], size - 1) {
+            rest.insert(0, item.clone());
+            result.push(rest);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::diff::PatchDiff;
+    use crate::solver::Solver;
+
+    // An empty board where the given cells have had their options reduced
+    pub(crate) fn board_with_options(options: &[(u8, &[u8])]) -> Board {
+        let mut board = Board::from_line(&".".repeat(81)).unwrap();
+        for (index, values) in options {
+            board.get_cell_mut(&BoardIndex::new(*index).unwrap()).options = values.to_vec();
+        }
+        board
+    }
+
+    // Checks every placement and elimination in the step against the solution
+    pub(crate) fn assert_step_is_sound(board: &Board, solution: &Board, step: &Step) {
+        let mut after = board.clone();
+        after.apply_diff(&step.diff);
+        for cell in after.cells.iter() {
+            let expected = solution.get_cell(&cell.index).value.unwrap();
+            match cell.value {
+                Some(value) => assert_eq!(value, expected, "{} placed a wrong value at {}", step.technique, cell.index),
+                None => assert!(
+                    cell.options.contains(&expected),
+                    "{} removed the solution from {}", step.technique, cell.index
+                ),
+            }
+        }
+    }
+
+    // Puzzles that need more than singles
+    pub(crate) const PUZZLES: &[&str] = &[
+        "400000938032094100095300240370609004529001673604703090957008300003900400240030709",
+        "000000000904607000076804100309701080008000300050308702007502610000403208000000000",
+        "017903600000080000900000507072010430000402070064370250701000065000030000005601720",
+        "100000569492056108056109240009640801064010000218035604040500016905061402621000005",
+        "900040000000600031020000090000700020002935600070002000060000073510009000000080009",
+        "4.....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......",
+        "..9748...7.........2.1.9.....7...24..64.1.59..98...3.....8.3.2.........6...2759..",
+    ];
+
+    #[test]
+    fn combinations_keep_order() {
+        assert_eq!(
+            combinations(&[1, 2, 3, 4], 2),
+            vec![vec![1, 2], vec![1, 3], vec![1, 4], vec![2, 3], vec![2, 4], vec![3, 4]]
+        );
+        assert_eq!(combinations(&[1, 2], 3), Vec::<Vec<i32>>::new());
+    }
+
+    #[test]
+    fn every_step_is_sound() {
+        for puzzle in PUZZLES {
+            let mut board = Board::from_line(puzzle).unwrap();
+            let solution = board.solve_exact_cover().unwrap();
+            board.prune_options().unwrap();
+            let solver = Solver::new();
+            while let Some(step) = solver.next_step(&board) {
+                assert_step_is_sound(&board, &solution, &step);
+                board.apply_diff(&step.diff);
+            }
+        }
+    }
+}
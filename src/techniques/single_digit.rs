@@ -0,0 +1,392 @@
+use crate::board::Board;
+use crate::board_index::BoardIndex;
+use crate::board_index::BoardLocation;
+use crate::board_index::House;
+use crate::techniques::peers;
+use crate::techniques::sees;
+use crate::techniques::subsets::positions;
+use crate::techniques::Step;
+
+// The only two cells in a house with the digit as a candidate. Exactly one of
+// them must hold the digit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConjugatePair {
+    pub a: BoardIndex,
+    pub b: BoardIndex,
+    pub house: House,
+}
+
+impl ConjugatePair {
+    // The end of the pair that isn't the given cell
+    pub fn other(&self, end: &BoardIndex) -> &BoardIndex {
+        if *end == self.a {
+            &self.b
+        } else {
+            &self.a
+        }
+    }
+
+    pub fn ends(&self) -> [&BoardIndex; 2] {
+        [&self.a, &self.b]
+    }
+}
+
+// The conjugate pairs for a digit, found in boxes, then rows, then columns.
+// Two cells sharing both a line and a box are linked once for each house, so
+// patterns that need a line link still find it. No two cells share two
+// houses of the same kind, so there are no other duplicates.
+pub fn conjugate_pairs(board: &Board, digit: u8) -> Vec<ConjugatePair> {
+    let mut pairs: Vec<ConjugatePair> = vec![];
+    for house in House::all() {
+        let cells = positions(board, &house, digit);
+        if cells.len() != 2 {
+            continue;
+        }
+        pairs.push(ConjugatePair { a: cells[0].clone(), b: cells[1].clone(), house });
+    }
+    pairs
+}
+
+// Removes the digit from every cell that sees both cells
+fn eliminate_seen_by_both(board: &Board, a: &BoardIndex, b: &BoardIndex, digit: u8, pattern: &[&BoardIndex]) -> Vec<(BoardIndex, u8)> {
+    peers(a)
+        .iter()
+        .filter(|i| sees(b, i) && !pattern.contains(i))
+        .filter(|i| board.has_candidate(i, digit))
+        .map(|i| (i.clone(), digit))
+        .collect()
+}
+
+fn pair_step(
+    board: &Board,
+    technique: &'static str,
+    digit: u8,
+    first: &ConjugatePair,
+    second: &ConjugatePair,
+    eliminations: &[(BoardIndex, u8)],
+) -> Step {
+    Step::new(
+        technique,
+        vec![first.a.clone(), first.b.clone(), second.a.clone(), second.b.clone()],
+        vec![digit],
+        vec![first.house, second.house],
+        board.elimination_diff(eliminations),
+    )
+}
+
+fn is_line(house: &House) -> bool {
+    !matches!(house, House::Box(_))
+}
+
+// Two conjugate pairs joined because one end of each sees the other. One of
+// the two far ends must hold the digit, so it can be removed from cells that
+// see both far ends. `accept` picks which pairs make up the pattern.
+fn find_two_link_pattern<F>(board: &Board, technique: &'static str, accept: F) -> Option<Step>
+where
+    F: Fn(&ConjugatePair, &BoardIndex, &ConjugatePair, &BoardIndex) -> bool,
+{
+    for digit in 1..=9 {
+        let pairs = conjugate_pairs(board, digit);
+        for (n, first) in pairs.iter().enumerate() {
+            for second in pairs.iter().skip(n + 1) {
+                for near_first in first.ends() {
+                    for near_second in second.ends() {
+                        let far_first = first.other(near_first);
+                        let far_second = second.other(near_second);
+                        let cells = [near_first, far_first, near_second, far_second];
+                        let distinct = (0..4).all(|i| (0..i).all(|j| cells[i] != cells[j]));
+                        if !distinct || !sees(near_first, near_second) || sees(far_first, far_second) {
+                            continue;
+                        }
+                        if !accept(first, near_first, second, near_second) {
+                            continue;
+                        }
+                        let eliminations = eliminate_seen_by_both(board, far_first, far_second, digit, &cells);
+                        if eliminations.is_empty() {
+                            continue;
+                        }
+                        return Some(pair_step(board, technique, digit, first, second, &eliminations));
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+// Two conjugate pairs in parallel rows (or columns) whose bases share a
+// column (or row)
+pub fn find_skyscraper(board: &Board) -> Option<Step> {
+    find_two_link_pattern(board, "Skyscraper", |first, near_first, second, near_second| {
+        match (first.house, second.house) {
+            (House::Row(_), House::Row(_)) => near_first.board_col() == near_second.board_col(),
+            (House::Col(_), House::Col(_)) => near_first.board_row() == near_second.board_row(),
+            _ => false,
+        }
+    })
+}
+
+// A conjugate pair in a row and one in a column with an end of each in the
+// same box
+pub fn find_two_string_kite(board: &Board) -> Option<Step> {
+    find_two_link_pattern(board, "2-String Kite", |first, near_first, second, near_second| {
+        let crossing = matches!(
+            (first.house, second.house),
+            (House::Row(_), House::Col(_)) | (House::Col(_), House::Row(_))
+        );
+        crossing && near_first.board_box() == near_second.board_box()
+    })
+}
+
+// Any two conjugate pairs joined by a weak link, which covers the cases that
+// aren't a Skyscraper or 2-String Kite
+pub fn find_turbot_fish(board: &Board) -> Option<Step> {
+    find_two_link_pattern(board, "Turbot Fish", |_, _, _, _| true)
+}
+
+// A box whose candidates for a digit all lie on one row and one column,
+// together with a conjugate pair in a line crossing that row (or column)
+// outside of the box. The digit can be removed where the pair's far line meets
+// the box's column (or row).
+pub fn find_empty_rectangle(board: &Board) -> Option<Step> {
+    for digit in 1..=9 {
+        let pairs = conjugate_pairs(board, digit);
+        for board_box in House::boxes() {
+            let cells = positions(board, &board_box, digit);
+            if cells.len() < 2 {
+                continue;
+            }
+            let House::Box(box_number) = board_box else { unreachable!() };
+            let box_rows = (box_number / 3 * 3)..(box_number / 3 * 3 + 3);
+            let box_cols = (box_number % 3 * 3)..(box_number % 3 * 3 + 3);
+
+            for row in box_rows.clone() {
+                for col in box_cols.clone() {
+                    let on_cross = cells.iter().all(|i| i.board_row() == row || i.board_col() == col);
+                    let one_line = cells.iter().all(|i| i.board_row() == row)
+                        || cells.iter().all(|i| i.board_col() == col);
+                    if !on_cross || one_line {
+                        continue;
+                    }
+
+                    for pair in pairs.iter().filter(|p| is_line(&p.house)) {
+                        for near in pair.ends() {
+                            let far = pair.other(near);
+                            let target = match pair.house {
+                                // The column pair meets the box's row, so the
+                                // far end's row meets the box's column
+                                House::Col(_) if near.board_row() == row
+                                    && !box_cols.contains(&near.board_col())
+                                    && !box_rows.contains(&far.board_row()) => far.board_row() * 9 + col,
+                                House::Row(_) if near.board_col() == col
+                                    && !box_rows.contains(&near.board_row())
+                                    && !box_cols.contains(&far.board_col()) => row * 9 + far.board_col(),
+                                _ => continue,
+                            };
+                            let target = BoardIndex::new(target).unwrap();
+                            if !board.has_candidate(&target, digit) {
+                                continue;
+                            }
+                            let mut pattern = cells.clone();
+                            pattern.extend([pair.a.clone(), pair.b.clone()]);
+                            return Some(Step::new(
+                                "Empty Rectangle",
+                                pattern,
+                                vec![digit],
+                                vec![board_box, House::Row(row), House::Col(col), pair.house],
+                                board.elimination_diff(&[(target, digit)]),
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+// Splits the conjugate pair graph for a digit into connected chains, giving
+// each cell one of two colors so that the ends of every pair differ
+fn color_chains(pairs: &[ConjugatePair]) -> Vec<Vec<(BoardIndex, bool)>> {
+    let mut chains: Vec<Vec<(BoardIndex, bool)>> = vec![];
+    for start in pairs.iter().map(|p| &p.a) {
+        if chains.iter().any(|c| c.iter().any(|(i, _)| i == start)) {
+            continue;
+        }
+        let mut chain: Vec<(BoardIndex, bool)> = vec![(start.clone(), true)];
+        let mut next = 0;
+        while next < chain.len() {
+            let (cell, color) = chain[next].clone();
+            for pair in pairs.iter().filter(|p| p.a == cell || p.b == cell) {
+                let other = pair.other(&cell);
+                if !chain.iter().any(|(i, _)| i == other) {
+                    chain.push((other.clone(), !color));
+                }
+            }
+            next += 1;
+        }
+        if chain.len() > 2 {
+            chains.push(chain);
+        }
+    }
+    chains
+}
+
+fn coloring_step(board: &Board, technique: &'static str, digit: u8, chain: &[(BoardIndex, bool)], eliminations: &[(BoardIndex, u8)]) -> Step {
+    Step::new(
+        technique,
+        chain.iter().map(|(i, _)| i.clone()).collect(),
+        vec![digit],
+        vec![],
+        board.elimination_diff(eliminations),
+    )
+}
+
+// Two cells of the same color see each other, so that color can't hold the
+// digit and it's removed from every cell of that color
+pub fn find_color_wrap(board: &Board) -> Option<Step> {
+    for digit in 1..=9 {
+        for chain in color_chains(&conjugate_pairs(board, digit)) {
+            for color in [true, false] {
+                let cells: Vec<&BoardIndex> = chain.iter().filter(|(_, c)| *c == color).map(|(i, _)| i).collect();
+                let wrapped = cells.iter().enumerate().any(|(n, a)| cells.iter().skip(n + 1).any(|b| sees(a, b)));
+                if !wrapped {
+                    continue;
+                }
+                let eliminations: Vec<(BoardIndex, u8)> = cells.iter().map(|i| ((*i).clone(), digit)).collect();
+                return Some(coloring_step(board, "Color Wrap", digit, &chain, &eliminations));
+            }
+        }
+    }
+    None
+}
+
+// A cell outside of the chain that sees both colors can't hold the digit,
+// since one of the colors must
+pub fn find_color_trap(board: &Board) -> Option<Step> {
+    for digit in 1..=9 {
+        for chain in color_chains(&conjugate_pairs(board, digit)) {
+            let eliminations: Vec<(BoardIndex, u8)> = board
+                .unsolved_indexes()
+                .into_iter()
+                .filter(|i| board.has_candidate(i, digit))
+                .filter(|i| !chain.iter().any(|(c, _)| c == i))
+                .filter(|i| {
+                    chain.iter().any(|(c, color)| *color && sees(c, i))
+                        && chain.iter().any(|(c, color)| !*color && sees(c, i))
+                })
+                .map(|i| (i, digit))
+                .collect();
+            if eliminations.is_empty() {
+                continue;
+            }
+            return Some(coloring_step(board, "Color Trap", digit, &chain, &eliminations));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::techniques::tests::board_with_options;
+
+    const WITHOUT_5: &[u8] = &[1, 2, 3, 4, 6, 7, 8, 9];
+
+    // An empty board where 5 is only a candidate in the given cells of the
+    // given rows
+    fn board_with_5_in_rows(rows: &[(u8, &[u8])]) -> Board {
+        let mut without: Vec<(u8, &[u8])> = vec![];
+        for (row, cols) in rows {
+            for col in 0..9 {
+                if !cols.contains(&col) {
+                    without.push((row * 9 + col, WITHOUT_5));
+                }
+            }
+        }
+        board_with_options(&without)
+    }
+
+    fn eliminated(step: &Step) -> Vec<u8> {
+        step.diff.fragments().iter().map(|f| f.index().value).collect()
+    }
+
+    #[test]
+    fn finds_conjugate_pairs() {
+        let board = board_with_5_in_rows(&[(0, &[1, 7])]);
+        let pairs = conjugate_pairs(&board, 5);
+        assert_eq!(pairs, vec![ConjugatePair {
+            a: BoardIndex::new(1).unwrap(),
+            b: BoardIndex::new(7).unwrap(),
+            house: House::Row(0),
+        }]);
+    }
+
+    #[test]
+    fn keeps_line_links_inside_a_box() {
+        // 5 only fits in r1c1 and r1c2, in both row 1 and box 1
+        let board = board_without_5(&[2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 18, 19, 20]);
+        let pairs = conjugate_pairs(&board, 5);
+        let houses: Vec<House> = pairs.iter().filter(|p| p.a.value == 0 && p.b.value == 1).map(|p| p.house).collect();
+        assert_eq!(houses, vec![House::Box(0), House::Row(0)]);
+    }
+
+    #[test]
+    fn finds_skyscraper() {
+        // Rows 1 and 6 have a base in column 1, with roofs at r1c5 and r6c6
+        let board = board_with_5_in_rows(&[(0, &[0, 4]), (5, &[0, 5])]);
+        let step = find_skyscraper(&board).unwrap();
+        assert_eq!(step.houses, vec![House::Row(0), House::Row(5)]);
+        assert_eq!(eliminated(&step), vec![14, 23, 31, 40]);
+    }
+
+    // An empty board where 5 is removed from the given cells
+    fn board_without_5(cells: &[u8]) -> Board {
+        let without: Vec<(u8, &[u8])> = cells.iter().map(|i| (*i, WITHOUT_5)).collect();
+        board_with_options(&without)
+    }
+
+    #[test]
+    fn finds_two_string_kite() {
+        // Row 1 has 5 in c2 and c7, column 1 has 5 in r3 and r8, and r1c2 and
+        // r3c1 share box 1
+        let board = board_without_5(&[0, 2, 3, 4, 5, 7, 8, 9, 27, 36, 45, 54, 72]);
+        assert!(find_skyscraper(&board).is_none());
+        let step = find_two_string_kite(&board).unwrap();
+        assert_eq!(step.houses, vec![House::Row(0), House::Col(0)]);
+        assert_eq!(eliminated(&step), vec![69]);
+    }
+
+    #[test]
+    fn finds_empty_rectangle() {
+        // Box 1 only has 5 in row 1 and column 1, and column 5 only has 5 in
+        // r1 and r7
+        let board = board_without_5(&[10, 11, 19, 20, 13, 22, 31, 40, 49, 67, 76]);
+        let step = find_empty_rectangle(&board).unwrap();
+        assert_eq!(step.technique, "Empty Rectangle");
+        assert_eq!(eliminated(&step), vec![54]);
+    }
+
+    #[test]
+    fn finds_color_trap() {
+        // r1c1 and r1c5 start a chain through columns 1 and 5 that ends at
+        // r7c1 and r7c5, which have opposite colors
+        let board = board_without_5(&[1, 2, 3, 5, 6, 7, 8, 9, 18, 27, 36, 45, 63, 72, 13, 22, 31, 40, 49, 67, 76]);
+        assert!(find_color_wrap(&board).is_none());
+        let step = find_color_trap(&board).unwrap();
+        assert_eq!(eliminated(&step), vec![55, 56, 57, 59, 60, 61, 62]);
+    }
+
+    #[test]
+    fn finds_color_wrap() {
+        // r1c1 and r2c2 get the same color and share box 1
+        let mut without: Vec<u8> = vec![];
+        without.extend([1, 2, 3, 5, 6, 7, 8]);
+        without.extend([13, 22, 31, 49, 58, 67, 76]);
+        without.extend([36, 38, 39, 41, 42, 43, 44]);
+        without.extend([1, 19, 28, 46, 55, 64, 73]);
+        let board = board_without_5(&without);
+        let step = find_color_wrap(&board).unwrap();
+        assert_eq!(eliminated(&step), vec![0, 40, 10]);
+    }
+}
@@ -0,0 +1,100 @@
+use crate::board::Board;
+use crate::board_index::BoardIndex;
+use crate::board_index::BoardLocation;
+use crate::board_index::House;
+use crate::techniques::Step;
+
+// An unsolved cell with only one candidate left
+pub fn find_naked_single(board: &Board) -> Option<Step> {
+    let index = board
+        .unsolved_indexes()
+        .into_iter()
+        .find(|i| board.candidates(i).len() == 1)?;
+    let value = board.candidates(&index)[0];
+    Some(Step::new(
+        "Naked Single",
+        vec![index.clone()],
+        vec![value],
+        vec![],
+        board.placement_diff(&index, value),
+    ))
+}
+
+// Finds the house of a given kind containing the index, along with the
+// index's peers in that house
+type HousePeers = fn(&BoardIndex) -> (House, Vec<BoardIndex>);
+
+// A candidate that appears in only one cell of a box, row or column. Boxes
+// are checked first since those are the easiest to spot.
+pub fn find_hidden_single(board: &Board) -> Option<Step> {
+    let unsolved = board.unsolved_indexes();
+    let house_peers: [HousePeers; 3] = [
+        |i| (House::Box(i.board_box()), i.entangled_box_indexes()),
+        |i| (House::Row(i.board_row()), i.entangled_row_indexes()),
+        |i| (House::Col(i.board_col()), i.entangled_col_indexes()),
+    ];
+
+    for house_peers in house_peers.iter() {
+        for index in &unsolved {
+            let (house, peers) = house_peers(index);
+            for value in board.candidates(index) {
+                if peers.iter().any(|p| board.has_candidate(p, *value)) {
+                    continue;
+                }
+                return Some(Step::new(
+                    "Hidden Single",
+                    vec![index.clone()],
+                    vec![*value],
+                    vec![house],
+                    board.placement_diff(index, *value),
+                ));
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::PatchDiff;
+
+    // Stalls with naked singles alone
+    const NEEDS_HIDDEN_SINGLES: &str = "200080300060070084030500209000105408000000000402706000301007040720040060004010003";
+
+    fn naked_singles_only(board: &mut Board) {
+        while let Some(step) = find_naked_single(board) {
+            board.apply_diff(&step.diff);
+        }
+    }
+
+    #[test]
+    fn finds_hidden_single_in_box() {
+        let mut board = Board::from_line(NEEDS_HIDDEN_SINGLES).unwrap();
+        board.prune_options().unwrap();
+        naked_singles_only(&mut board);
+        assert!(!board.unsolved_indexes().is_empty());
+
+        let step = find_hidden_single(&board).unwrap();
+        let house = step.houses[0];
+        let value = step.digits[0];
+        assert!(matches!(house, House::Box(_)));
+        assert!(house.contains(&step.cells[0]));
+        let places: Vec<BoardIndex> = house
+            .indexes()
+            .into_iter()
+            .filter(|i| board.has_candidate(i, value))
+            .collect();
+        assert_eq!(places, step.cells);
+    }
+
+    #[test]
+    fn solves_with_singles() {
+        let mut board = Board::from_line(NEEDS_HIDDEN_SINGLES).unwrap();
+        board.prune_options().unwrap();
+        while let Some(step) = find_naked_single(&board).or_else(|| find_hidden_single(&board)) {
+            board.apply_diff(&step.diff);
+        }
+        assert!(board.unsolved_indexes().is_empty());
+    }
+}
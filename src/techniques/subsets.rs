@@ -0,0 +1,199 @@
+use crate::board::Board;
+use crate::board_index::BoardIndex;
+use crate::board_index::House;
+use crate::techniques::combinations;
+use crate::techniques::Step;
+
+fn subset_name(size: usize, naked: bool) -> &'static str {
+    match (size, naked) {
+        (2, true) => "Naked Pair",
+        (3, true) => "Naked Triple",
+        (4, true) => "Naked Quad",
+        (2, false) => "Hidden Pair",
+        (3, false) => "Hidden Triple",
+        (4, false) => "Hidden Quad",
+        _ => unreachable!("subsets are between 2 and 4 cells"),
+    }
+}
+
+// The cells in the house that have the value as a candidate
+pub(crate) fn positions(board: &Board, house: &House, value: u8) -> Vec<BoardIndex> {
+    house
+        .indexes()
+        .into_iter()
+        .filter(|i| board.has_candidate(i, value))
+        .collect()
+}
+
+// N cells in a house whose candidates are limited to the same N digits. Those
+// digits must go in those cells, so they can be removed from the rest of the
+// house.
+pub fn find_naked_subset(board: &Board, size: usize) -> Option<Step> {
+    for house in House::all() {
+        let indexes = house.indexes();
+        let cells: Vec<BoardIndex> = indexes
+            .iter()
+            .filter(|i| (2..=size).contains(&board.candidates(i).len()))
+            .cloned()
+            .collect();
+
+        for subset in combinations(&cells, size) {
+            let mut digits: Vec<u8> = subset
+                .iter()
+                .flat_map(|i| board.candidates(i).to_vec())
+                .collect();
+            digits.sort();
+            digits.dedup();
+            if digits.len() != size {
+                continue;
+            }
+
+            let eliminations: Vec<(BoardIndex, u8)> = indexes
+                .iter()
+                .filter(|i| !subset.contains(i))
+                .flat_map(|i| digits.iter().map(move |d| (i.clone(), *d)))
+                .filter(|(i, d)| board.has_candidate(i, *d))
+                .collect();
+            if eliminations.is_empty() {
+                continue;
+            }
+
+            return Some(Step::new(
+                subset_name(size, true),
+                subset,
+                digits,
+                vec![house],
+                board.elimination_diff(&eliminations),
+            ));
+        }
+    }
+    None
+}
+
+// N digits whose candidates within a house are limited to the same N cells.
+// Those cells must hold those digits, so their other candidates can be
+// removed.
+pub fn find_hidden_subset(board: &Board, size: usize) -> Option<Step> {
+    hidden_subsets(board, size).next()
+}
+
+// Every hidden subset of the size on the board, in the order
+// `find_hidden_subset` looks
+pub fn hidden_subsets(board: &Board, size: usize) -> impl Iterator<Item = Step> + '_ {
+    House::all().into_iter().flat_map(move |house| {
+        let digits: Vec<u8> = (1..=9)
+            .filter(|d| (2..=size).contains(&positions(board, &house, *d).len()))
+            .collect();
+        combinations(&digits, size)
+            .into_iter()
+            .filter_map(move |subset| hidden_subset(board, house, subset))
+    })
+}
+
+fn hidden_subset(board: &Board, house: House, subset: Vec<u8>) -> Option<Step> {
+    let mut cells: Vec<BoardIndex> = subset
+        .iter()
+        .flat_map(|d| positions(board, &house, *d))
+        .collect();
+    cells.sort();
+    cells.dedup();
+    if cells.len() != subset.len() {
+        return None;
+    }
+
+    let eliminations: Vec<(BoardIndex, u8)> = cells
+        .iter()
+        .flat_map(|i| board.candidates(i).iter().map(move |d| (i.clone(), *d)))
+        .filter(|(_, d)| !subset.contains(d))
+        .collect();
+    if eliminations.is_empty() {
+        return None;
+    }
+
+    Some(Step::new(
+        subset_name(subset.len(), false),
+        cells,
+        subset,
+        vec![house],
+        board.elimination_diff(&eliminations),
+    ))
+}
+
+pub fn find_naked_pair(board: &Board) -> Option<Step> {
+    find_naked_subset(board, 2)
+}
+
+pub fn find_naked_triple(board: &Board) -> Option<Step> {
+    find_naked_subset(board, 3)
+}
+
+pub fn find_naked_quad(board: &Board) -> Option<Step> {
+    find_naked_subset(board, 4)
+}
+
+pub fn find_hidden_pair(board: &Board) -> Option<Step> {
+    find_hidden_subset(board, 2)
+}
+
+pub fn find_hidden_triple(board: &Board) -> Option<Step> {
+    find_hidden_subset(board, 3)
+}
+
+pub fn find_hidden_quad(board: &Board) -> Option<Step> {
+    find_hidden_subset(board, 4)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::techniques::tests::board_with_options;
+    use crate::diff::PatchDiff;
+
+    #[test]
+    fn finds_naked_pair_in_row() {
+        let board = board_with_options(&[(3, &[4, 7]), (6, &[4, 7])]);
+        let step = find_naked_pair(&board).unwrap();
+        assert_eq!(step.houses, vec![House::Row(0)]);
+        assert_eq!(step.digits, vec![4, 7]);
+        assert_eq!(step.cells.iter().map(|i| i.value).collect::<Vec<u8>>(), vec![3, 6]);
+
+        let mut after = board.clone();
+        after.apply_diff(&step.diff);
+        for i in House::Row(0).indexes() {
+            if i.value != 3 && i.value != 6 {
+                assert!(!after.has_candidate(&i, 4));
+                assert!(!after.has_candidate(&i, 7));
+            }
+        }
+        assert!(after.has_candidate(&BoardIndex::new(9).unwrap(), 4));
+    }
+
+    #[test]
+    fn finds_naked_triple_with_partial_cells() {
+        // {1,2}, {2,3} and {1,3} share three digits in column 0
+        let board = board_with_options(&[(0, &[1, 2]), (27, &[2, 3]), (72, &[1, 3])]);
+        assert!(find_naked_pair(&board).is_none());
+        let step = find_naked_triple(&board).unwrap();
+        assert_eq!(step.houses, vec![House::Col(0)]);
+        assert_eq!(step.digits, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn finds_hidden_pair_in_box() {
+        // 8 and 9 only fit in cells 0 and 10 of box 0
+        let mut without: Vec<(u8, &[u8])> = vec![];
+        for i in [1, 2, 9, 11, 18, 19, 20] {
+            without.push((i, &[1, 2, 3, 4, 5, 6, 7]));
+        }
+        let board = board_with_options(&without);
+        let step = find_hidden_pair(&board).unwrap();
+        assert_eq!(step.houses, vec![House::Box(0)]);
+        assert_eq!(step.digits, vec![8, 9]);
+        assert_eq!(step.cells.iter().map(|i| i.value).collect::<Vec<u8>>(), vec![0, 10]);
+
+        let mut after = board.clone();
+        after.apply_diff(&step.diff);
+        assert_eq!(after.candidates(&BoardIndex::new(0).unwrap()), &[8, 9]);
+        assert_eq!(after.candidates(&BoardIndex::new(10).unwrap()), &[8, 9]);
+    }
+}
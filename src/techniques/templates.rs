@@ -0,0 +1,143 @@
+use crate::board::Board;
+use crate::board_index::BoardIndex;
+use crate::techniques::CellMask;
+use crate::techniques::Step;
+
+// What the templates of a digit have in common: how many there are, the
+// cells any of them use and the cells all of them use
+struct Templates {
+    count: usize,
+    any: CellMask,
+    all: CellMask,
+}
+
+// Whether the digit could go in the cell, either because it's already there
+// or because it's still a candidate
+fn fits(board: &Board, index: &BoardIndex, digit: u8) -> bool {
+    let cell = board.get_cell(index);
+    match cell.value {
+        Some(value) => value == digit,
+        None => cell.options.contains(&digit),
+    }
+}
+
+// Places the digit in each row in turn, in a column and box no earlier row
+// used, recording every complete placement
+fn place_rows(board: &Board, digit: u8, row: u8, cols: u16, boxes: u16, used: CellMask, found: &mut Templates) {
+    if row == 9 {
+        found.count += 1;
+        found.any |= used;
+        found.all &= used;
+        return;
+    }
+    let placed = (0..9).find(|col| board.cells[(row * 9 + col) as usize].value == Some(digit));
+    for col in 0..9 {
+        let board_box = row / 3 * 3 + col / 3;
+        if cols & 1 << col != 0 || boxes & 1 << board_box != 0 || placed.is_some_and(|p| p != col) {
+            continue;
+        }
+        let index = BoardIndex::new(row * 9 + col).unwrap();
+        if !fits(board, &index, digit) {
+            continue;
+        }
+        let used = used | 1 << index.value;
+        place_rows(board, digit, row + 1, cols | 1 << col, boxes | 1 << board_box, used, found);
+    }
+}
+
+// Every way of placing the digit once in each row, column and box that agrees
+// with the board
+fn templates(board: &Board, digit: u8) -> Templates {
+    let mut found = Templates { count: 0, any: 0, all: CellMask::MAX };
+    place_rows(board, digit, 0, 0, 0, 0, &mut found);
+    found
+}
+
+// How many templates fit each digit, with the count for the digit n at n - 1.
+// There are 46,656 for a digit on an empty board, and exactly one for a
+// digit the board has fully placed.
+pub fn template_counts(board: &Board) -> [usize; 9] {
+    let mut counts = [0; 9];
+    for digit in 1..=9 {
+        counts[digit as usize - 1] = templates(board, digit).count;
+    }
+    counts
+}
+
+// The pattern overlay method. A candidate that no template of its digit uses
+// can be removed, and an unsolved cell that every template uses must take the
+// digit.
+pub fn find_template(board: &Board) -> Option<Step> {
+    for digit in 1..=9 {
+        let found = templates(board, digit);
+        if found.count == 0 {
+            continue;
+        }
+        let unsolved: Vec<BoardIndex> = board
+            .unsolved_indexes()
+            .into_iter()
+            .filter(|i| board.has_candidate(i, digit))
+            .collect();
+
+        let unused: Vec<BoardIndex> = unsolved.iter().filter(|i| found.any & 1 << i.value == 0).cloned().collect();
+        if !unused.is_empty() {
+            let labels: Vec<String> = unused.iter().map(|i| i.label()).collect();
+            let eliminations: Vec<(BoardIndex, u8)> = unused.iter().map(|i| (i.clone(), digit)).collect();
+            return Some(Step {
+                proof: vec![format!("{} templates fit {}, and none of them use {}", found.count, digit, labels.join(", "))],
+                ..Step::new("Template", unused, vec![digit], vec![], board.elimination_diff(&eliminations))
+            });
+        }
+
+        if let Some(index) = unsolved.into_iter().find(|i| found.all & 1 << i.value != 0) {
+            return Some(Step {
+                proof: vec![format!("{} templates fit {}, and all of them use {}", found.count, digit, index.label())],
+                ..Step::new(
+                    "Template",
+                    vec![index.clone()],
+                    vec![digit],
+                    vec![],
+                    board.placement_diff(&index, digit),
+                )
+            });
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::PatchDiff;
+    use crate::techniques::tests::board_with_options;
+
+    #[test]
+    fn counts_templates() {
+        let empty = board_with_options(&[]);
+        assert_eq!(template_counts(&empty), [46656; 9]);
+
+        let mut board = Board::from_line(".4.6.2.31.....16.96..54.827..276..8.5.6....74.87..5.6216..8..5.82...7.9.7....62..").unwrap();
+        board.prune_options().unwrap();
+        let counts = template_counts(&board);
+        assert!(counts.iter().all(|c| *c >= 1));
+        board.search().unwrap();
+        assert_eq!(template_counts(&board), [1; 9]);
+    }
+
+    #[test]
+    fn removes_candidates_no_template_uses() {
+        // 5 can only go in r1c1 or r1c2 within row 1, so every template puts
+        // the 5 of box 1 in row 1 and none can put one in r2c1 to r3c3
+        let without_5: &[u8] = &[1, 2, 3, 4, 6, 7, 8, 9];
+        let without: Vec<(u8, &[u8])> = (2..9).map(|i| (i, without_5)).collect();
+        let board = board_with_options(&without);
+        let step = find_template(&board).unwrap();
+        assert_eq!(step.digits, vec![5]);
+        let mut after = board.clone();
+        after.apply_diff(&step.diff);
+        for i in [9, 10, 11, 18, 19, 20] {
+            assert!(!after.has_candidate(&BoardIndex::new(i).unwrap(), 5));
+        }
+        assert!(after.has_candidate(&BoardIndex::new(0).unwrap(), 5));
+    }
+}
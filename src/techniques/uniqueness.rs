@@ -0,0 +1,500 @@
+use crate::board::Board;
+use crate::board_index::BoardIndex;
+use crate::board_index::BoardLocation;
+use crate::board_index::House;
+use crate::techniques::combinations;
+use crate::techniques::peers;
+use crate::techniques::sees;
+use crate::techniques::subsets::positions;
+use crate::techniques::Step;
+
+// Four unsolved cells in two rows, two columns and two boxes that all have
+// the same two digits as candidates. If all four were left with just those
+// digits they could be swapped, giving a second solution, so a puzzle with a
+// single solution can't end up that way.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rectangle {
+    // Top-left, top-right, bottom-left and bottom-right
+    pub cells: [BoardIndex; 4],
+    pub digits: [u8; 2],
+}
+
+impl Rectangle {
+    // The corner diagonally opposite the given one
+    fn opposite(corner: usize) -> usize {
+        3 - corner
+    }
+
+    // The corners that have candidates other than the two digits
+    fn roofs(&self, board: &Board) -> Vec<usize> {
+        (0..4).filter(|c| board.candidates(&self.cells[*c]).len() > 2).collect()
+    }
+
+    // The candidates of the corner other than the two digits
+    fn extras(&self, board: &Board, corner: usize) -> Vec<u8> {
+        board
+            .candidates(&self.cells[corner])
+            .iter()
+            .filter(|d| !self.digits.contains(d))
+            .cloned()
+            .collect()
+    }
+
+    fn corners(&self, corners: &[usize]) -> Vec<BoardIndex> {
+        corners.iter().map(|c| self.cells[*c].clone()).collect()
+    }
+
+    // The houses containing both corners
+    fn shared_houses(&self, a: usize, b: usize) -> Vec<House> {
+        let b = &self.cells[b];
+        self.cells[a].houses().into_iter().filter(|h| h.contains(b)).collect()
+    }
+
+    fn step(&self, board: &Board, technique: &'static str, houses: Vec<House>, eliminations: &[(BoardIndex, u8)]) -> Step {
+        Step {
+            assumes_unique: true,
+            ..Step::new(
+                technique,
+                self.cells.to_vec(),
+                self.digits.to_vec(),
+                houses,
+                board.elimination_diff(eliminations),
+            )
+        }
+    }
+}
+
+// Every rectangle on the board, from the top-left
+pub fn rectangles(board: &Board) -> Vec<Rectangle> {
+    let mut found = vec![];
+    for rows in combinations(&(0..9).collect::<Vec<u8>>(), 2) {
+        for cols in combinations(&(0..9).collect::<Vec<u8>>(), 2) {
+            // Exactly two boxes, so the rectangle shares either a band or a
+            // stack but not both
+            if (rows[0] / 3 == rows[1] / 3) == (cols[0] / 3 == cols[1] / 3) {
+                continue;
+            }
+            let cells = [
+                BoardIndex::new(rows[0] * 9 + cols[0]).unwrap(),
+                BoardIndex::new(rows[0] * 9 + cols[1]).unwrap(),
+                BoardIndex::new(rows[1] * 9 + cols[0]).unwrap(),
+                BoardIndex::new(rows[1] * 9 + cols[1]).unwrap(),
+            ];
+            let shared: Vec<u8> = board
+                .candidates(&cells[0])
+                .iter()
+                .filter(|d| cells[1..].iter().all(|c| board.has_candidate(c, **d)))
+                .cloned()
+                .collect();
+            for digits in combinations(&shared, 2) {
+                found.push(Rectangle { cells: cells.clone(), digits: [digits[0], digits[1]] });
+            }
+        }
+    }
+    found
+}
+
+// Cells outside of the given ones that see all of them and have the digit
+fn seen_by_all(board: &Board, cells: &[BoardIndex], digit: u8) -> Vec<(BoardIndex, u8)> {
+    peers(&cells[0])
+        .iter()
+        .filter(|i| !cells.contains(i))
+        .filter(|i| cells.iter().all(|c| sees(c, i)))
+        .filter(|i| board.has_candidate(i, digit))
+        .map(|i| (i.clone(), digit))
+        .collect()
+}
+
+// The extra digit if every given corner has exactly one extra, and it's the
+// same one
+fn single_shared_extra(board: &Board, rectangle: &Rectangle, roofs: &[usize]) -> Option<u8> {
+    let extras: Vec<Vec<u8>> = roofs.iter().map(|r| rectangle.extras(board, *r)).collect();
+    if extras.iter().all(|e| e.len() == 1 && e[0] == extras[0][0]) {
+        Some(extras[0][0])
+    } else {
+        None
+    }
+}
+
+// Three corners only have the two digits, so the fourth can't be either of
+// them
+pub fn find_unique_rectangle_type_1(board: &Board) -> Option<Step> {
+    for rectangle in rectangles(board) {
+        let roofs = rectangle.roofs(board);
+        if roofs.len() != 1 {
+            continue;
+        }
+        let roof = rectangle.cells[roofs[0]].clone();
+        let eliminations: Vec<(BoardIndex, u8)> = rectangle.digits.iter().map(|d| (roof.clone(), *d)).collect();
+        return Some(rectangle.step(board, "Unique Rectangle Type 1", vec![], &eliminations));
+    }
+    None
+}
+
+// Two corners in the same row or column have one extra candidate, the same
+// in both. One of them must be that digit, so it can be removed from every
+// cell that sees both.
+pub fn find_unique_rectangle_type_2(board: &Board) -> Option<Step> {
+    for rectangle in rectangles(board) {
+        let roofs = rectangle.roofs(board);
+        if roofs.len() != 2 || rectangle.shared_houses(roofs[0], roofs[1]).is_empty() {
+            continue;
+        }
+        let extra = match single_shared_extra(board, &rectangle, &roofs) {
+            Some(extra) => extra,
+            None => continue,
+        };
+        let eliminations = seen_by_all(board, &rectangle.corners(&roofs), extra);
+        if !eliminations.is_empty() {
+            return Some(rectangle.step(board, "Unique Rectangle Type 2", vec![], &eliminations));
+        }
+    }
+    None
+}
+
+// Two corners in the same row or column have extra candidates, and one of
+// them must be an extra. Together they act like a single cell with the
+// extras as candidates, which can form a naked subset with other cells of a
+// house they share.
+pub fn find_unique_rectangle_type_3(board: &Board) -> Option<Step> {
+    for rectangle in rectangles(board) {
+        let roofs = rectangle.roofs(board);
+        if roofs.len() != 2 {
+            continue;
+        }
+        let mut extras = [rectangle.extras(board, roofs[0]), rectangle.extras(board, roofs[1])].concat();
+        extras.sort();
+        extras.dedup();
+        if extras.len() < 2 {
+            continue;
+        }
+        let roof_cells = rectangle.corners(&roofs);
+        for house in rectangle.shared_houses(roofs[0], roofs[1]) {
+            let others: Vec<BoardIndex> = house
+                .indexes()
+                .into_iter()
+                .filter(|i| !roof_cells.contains(i))
+                .filter(|i| !board.candidates(i).is_empty())
+                .collect();
+            for size in 1..=3 {
+                for subset in combinations(&others, size) {
+                    let mut digits = extras.clone();
+                    for cell in &subset {
+                        digits.extend(board.candidates(cell));
+                    }
+                    digits.sort();
+                    digits.dedup();
+                    if digits.len() != size + 1 {
+                        continue;
+                    }
+                    let eliminations: Vec<(BoardIndex, u8)> = others
+                        .iter()
+                        .filter(|i| !subset.contains(i))
+                        .flat_map(|i| digits.iter().map(move |d| (i.clone(), *d)))
+                        .filter(|(i, d)| board.has_candidate(i, *d))
+                        .collect();
+                    if !eliminations.is_empty() {
+                        let mut cells = rectangle.cells.to_vec();
+                        cells.extend(subset);
+                        let mut step = rectangle.step(board, "Unique Rectangle Type 3", vec![house], &eliminations);
+                        step.cells = cells;
+                        return Some(step);
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+// Two corners in the same row or column have extra candidates, and one of
+// the two digits can only go in those corners within a house they share.
+// One of them must be that digit, so neither can be the other digit.
+pub fn find_unique_rectangle_type_4(board: &Board) -> Option<Step> {
+    for rectangle in rectangles(board) {
+        let roofs = rectangle.roofs(board);
+        if roofs.len() != 2 {
+            continue;
+        }
+        let roof_cells = rectangle.corners(&roofs);
+        for house in rectangle.shared_houses(roofs[0], roofs[1]) {
+            for (n, digit) in rectangle.digits.iter().enumerate() {
+                if positions(board, &house, *digit) != roof_cells {
+                    continue;
+                }
+                let other = rectangle.digits[1 - n];
+                let eliminations: Vec<(BoardIndex, u8)> = roof_cells.iter().map(|c| (c.clone(), other)).collect();
+                return Some(rectangle.step(board, "Unique Rectangle Type 4", vec![house], &eliminations));
+            }
+        }
+    }
+    None
+}
+
+// Two diagonal corners, or three corners, have the same single extra
+// candidate. One of them must be that digit, so it can be removed from every
+// cell that sees all of them.
+pub fn find_unique_rectangle_type_5(board: &Board) -> Option<Step> {
+    for rectangle in rectangles(board) {
+        let roofs = rectangle.roofs(board);
+        let diagonal = roofs.len() == 2 && rectangle.shared_houses(roofs[0], roofs[1]).is_empty();
+        if !diagonal && roofs.len() != 3 {
+            continue;
+        }
+        let extra = match single_shared_extra(board, &rectangle, &roofs) {
+            Some(extra) => extra,
+            None => continue,
+        };
+        let eliminations = seen_by_all(board, &rectangle.corners(&roofs), extra);
+        if !eliminations.is_empty() {
+            return Some(rectangle.step(board, "Unique Rectangle Type 5", vec![], &eliminations));
+        }
+    }
+    None
+}
+
+// Two diagonal corners have extra candidates, and one of the digits can only
+// go in the rectangle within both of its rows, or both of its columns. If
+// either diagonal corner was that digit, the other would have to be too,
+// leaving the other two corners with the second digit, so neither can be it.
+pub fn find_unique_rectangle_type_6(board: &Board) -> Option<Step> {
+    for rectangle in rectangles(board) {
+        let roofs = rectangle.roofs(board);
+        if roofs.len() != 2 || !rectangle.shared_houses(roofs[0], roofs[1]).is_empty() {
+            continue;
+        }
+        let cells = &rectangle.cells;
+        let rows = [House::Row(cells[0].board_row()), House::Row(cells[3].board_row())];
+        let cols = [House::Col(cells[0].board_col()), House::Col(cells[3].board_col())];
+        for digit in rectangle.digits {
+            let in_rectangle = |house: &House| positions(board, house, digit).iter().all(|i| cells.contains(i));
+            let houses = if rows.iter().all(in_rectangle) {
+                rows
+            } else if cols.iter().all(in_rectangle) {
+                cols
+            } else {
+                continue;
+            };
+            let eliminations: Vec<(BoardIndex, u8)> = rectangle.corners(&roofs).into_iter().map(|c| (c, digit)).collect();
+            return Some(rectangle.step(board, "Unique Rectangle Type 6", houses.to_vec(), &eliminations));
+        }
+    }
+    None
+}
+
+// One corner only has the two digits, and one of them can only go in the
+// rectangle within both the row and the column of the opposite corner. If
+// the opposite corner was the other digit, the rectangle would be left with
+// just the two digits, so it can't be.
+pub fn find_hidden_unique_rectangle(board: &Board) -> Option<Step> {
+    for rectangle in rectangles(board) {
+        for corner in 0..4 {
+            if board.candidates(&rectangle.cells[corner]).len() != 2 {
+                continue;
+            }
+            let opposite = &rectangle.cells[Rectangle::opposite(corner)];
+            let houses = vec![House::Row(opposite.board_row()), House::Col(opposite.board_col())];
+            for (n, digit) in rectangle.digits.iter().enumerate() {
+                let linked = houses.iter().all(|h| {
+                    positions(board, h, *digit).iter().all(|i| rectangle.cells.contains(i))
+                });
+                let other = rectangle.digits[1 - n];
+                if linked && board.has_candidate(opposite, other) {
+                    let eliminations = [(opposite.clone(), other)];
+                    return Some(rectangle.step(board, "Hidden Unique Rectangle", houses, &eliminations));
+                }
+            }
+        }
+    }
+    None
+}
+
+// Every unsolved cell has two candidates except one with three, and every
+// candidate appears twice in each of its houses except one digit of that
+// cell. Without that digit the board would have at least two solutions, so
+// the cell must be that digit.
+pub fn find_bug_plus_one(board: &Board) -> Option<Step> {
+    let unsolved = board.unsolved_indexes();
+    let mut extra_cells = unsolved.iter().filter(|i| board.candidates(i).len() != 2);
+    let cell = extra_cells.next()?.clone();
+    if extra_cells.next().is_some() || board.candidates(&cell).len() != 3 {
+        return None;
+    }
+
+    // The digit of the cell that appears three times in its houses
+    let houses = cell.houses();
+    let digit = *board
+        .candidates(&cell)
+        .iter()
+        .find(|d| houses.iter().all(|h| positions(board, h, **d).len() == 3))?;
+    for house in House::all() {
+        for d in 1..=9 {
+            let count = positions(board, &house, d).len();
+            let expected = if d == digit && houses.contains(&house) { 3 } else { 2 };
+            if count != 0 && count != expected {
+                return None;
+            }
+        }
+    }
+
+    Some(Step {
+        assumes_unique: true,
+        ..Step::new(
+            "BUG+1",
+            vec![cell.clone()],
+            vec![digit],
+            houses.to_vec(),
+            board.placement_diff(&cell, digit),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::PatchDiff;
+    use crate::solver::Solver;
+    use crate::techniques::tests::board_with_options;
+
+    const WITHOUT_1: &[u8] = &[2, 3, 4, 5, 6, 7, 8, 9];
+
+    fn eliminated(step: &Step) -> Vec<u8> {
+        step.diff.fragments().iter().map(|f| f.index().value).collect()
+    }
+
+    fn apply(board: &Board, step: &Step) -> Board {
+        let mut after = board.clone();
+        after.apply_diff(&step.diff);
+        after
+    }
+
+    #[test]
+    fn finds_rectangles_in_two_boxes() {
+        // r1c1, r1c2, r4c1 and r4c2, which span boxes 1 and 4
+        let board = board_with_options(&[(0, &[1, 2]), (1, &[1, 2]), (27, &[1, 2]), (28, &[1, 2])]);
+        let found = rectangles(&board);
+        assert!(found.contains(&Rectangle {
+            cells: [0, 1, 27, 28].map(|i| BoardIndex::new(i).unwrap()),
+            digits: [1, 2],
+        }));
+        // r1c1, r1c2, r2c1 and r2c2 are all in box 1
+        assert!(!found.iter().any(|r| r.cells[0].value == 0 && r.cells[3].value == 10));
+    }
+
+    #[test]
+    fn finds_type_1() {
+        let board = board_with_options(&[(0, &[1, 2]), (1, &[1, 2]), (27, &[1, 2]), (28, &[1, 2, 5])]);
+        let step = find_unique_rectangle_type_1(&board).unwrap();
+        assert!(step.assumes_unique);
+        assert_eq!(apply(&board, &step).cells[28].options, vec![5]);
+    }
+
+    #[test]
+    fn finds_type_2() {
+        // The roofs r4c1 and r4c2 share row 4 and box 4, and one of them is a 5
+        let board = board_with_options(&[(0, &[1, 2]), (1, &[1, 2]), (27, &[1, 2, 5]), (28, &[1, 2, 5])]);
+        assert!(find_unique_rectangle_type_1(&board).is_none());
+        let step = find_unique_rectangle_type_2(&board).unwrap();
+        assert_eq!(eliminated(&step), vec![29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 45, 46, 47]);
+    }
+
+    #[test]
+    fn finds_type_3() {
+        // The roofs act as a cell with {5, 6}, making a naked pair with r4c9
+        let board = board_with_options(&[
+            (0, &[1, 2]),
+            (1, &[1, 2]),
+            (27, &[1, 2, 5]),
+            (28, &[1, 2, 6]),
+            (35, &[5, 6]),
+        ]);
+        let step = find_unique_rectangle_type_3(&board).unwrap();
+        assert_eq!(step.houses, vec![House::Row(3)]);
+        assert_eq!(eliminated(&step), vec![29, 30, 31, 32, 33, 34]);
+    }
+
+    #[test]
+    fn finds_type_4() {
+        // 1 can only go in the roofs within row 4, so neither can be a 2
+        let mut options: Vec<(u8, &[u8])> = vec![(0, &[1, 2]), (1, &[1, 2]), (27, &[1, 2, 5]), (28, &[1, 2, 6])];
+        options.extend((29..36).map(|i| (i, WITHOUT_1)));
+        let board = board_with_options(&options);
+        let step = find_unique_rectangle_type_4(&board).unwrap();
+        assert_eq!(step.houses, vec![House::Row(3)]);
+        let after = apply(&board, &step);
+        assert_eq!(after.cells[27].options, vec![1, 5]);
+        assert_eq!(after.cells[28].options, vec![1, 6]);
+    }
+
+    #[test]
+    fn finds_type_5() {
+        // The diagonal roofs r1c2 and r4c1 both have an extra 5
+        let board = board_with_options(&[(0, &[1, 2]), (1, &[1, 2, 5]), (27, &[1, 2, 5]), (28, &[1, 2])]);
+        assert!(find_unique_rectangle_type_2(&board).is_none());
+        let step = find_unique_rectangle_type_5(&board).unwrap();
+        assert_eq!(eliminated(&step), vec![9, 18, 37, 46]);
+    }
+
+    #[test]
+    fn finds_type_6() {
+        // 1 can only go in the rectangle within rows 1 and 4
+        let mut options: Vec<(u8, &[u8])> = vec![(0, &[1, 2]), (1, &[1, 2, 5]), (27, &[1, 2, 6]), (28, &[1, 2])];
+        options.extend((2..9).chain(29..36).map(|i| (i, WITHOUT_1)));
+        let board = board_with_options(&options);
+        let step = find_unique_rectangle_type_6(&board).unwrap();
+        assert_eq!(step.houses, vec![House::Row(0), House::Row(3)]);
+        let after = apply(&board, &step);
+        assert_eq!(after.cells[1].options, vec![2, 5]);
+        assert_eq!(after.cells[27].options, vec![2, 6]);
+    }
+
+    #[test]
+    fn finds_hidden_unique_rectangle() {
+        // r1c1 only has {1, 2}, and 1 can only go in the rectangle within row
+        // 4 and column 2, so r4c2 can't be a 2
+        let mut options: Vec<(u8, &[u8])> = vec![(0, &[1, 2])];
+        options.extend((29..36).chain((10..81).step_by(9).filter(|i| *i != 28)).map(|i| (i, WITHOUT_1)));
+        let board = board_with_options(&options);
+        let step = find_hidden_unique_rectangle(&board).unwrap();
+        assert_eq!(step.houses, vec![House::Row(3), House::Col(1)]);
+        assert_eq!(eliminated(&step), vec![28]);
+    }
+
+    #[test]
+    fn finds_bug_plus_one() {
+        // A rectangle of {1, 2} and a loop of {3, 4}, where r1c1 also has a 3.
+        // Every other cell is solved.
+        let mut board = Board::from_line(&"9".repeat(81)).unwrap();
+        let unsolved: [(u8, &[u8]); 10] = [
+            (0, &[1, 2, 3]),
+            (1, &[1, 2]),
+            (27, &[1, 2]),
+            (28, &[1, 2]),
+            (2, &[3, 4]),
+            (5, &[3, 4]),
+            (9, &[3, 4]),
+            (14, &[3, 4]),
+            (36, &[3, 4]),
+            (38, &[3, 4]),
+        ];
+        for (index, options) in unsolved {
+            let cell = board.get_cell_mut(&BoardIndex::new(index).unwrap());
+            cell.value = None;
+            cell.options = options.to_vec();
+        }
+        let step = find_bug_plus_one(&board).unwrap();
+        assert_eq!(step.digits, vec![3]);
+        assert_eq!(apply(&board, &step).cells[0].value, Some(3));
+
+        // A second cell with three candidates isn't a BUG+1
+        board.get_cell_mut(&BoardIndex::new(2).unwrap()).options = vec![3, 4, 5];
+        assert!(find_bug_plus_one(&board).is_none());
+    }
+
+    #[test]
+    fn uniqueness_techniques_are_opt_in() {
+        let board = board_with_options(&[(0, &[1, 2]), (1, &[1, 2]), (27, &[1, 2]), (28, &[1, 2, 5])]);
+        assert!(Solver::new().next_step(&board).is_none_or(|step| !step.assumes_unique));
+        assert!(Solver::assuming_unique().next_step(&board).is_some());
+    }
+}
@@ -0,0 +1,270 @@
+use crate::board::Board;
+use crate::board_index::BoardIndex;
+use crate::board_index::House;
+use crate::techniques::combinations;
+use crate::techniques::peers;
+use crate::techniques::sees;
+use crate::techniques::subsets::positions;
+use crate::techniques::Step;
+
+// Unsolved cells with between `min` and `max` candidates
+fn cells_with_candidates(board: &Board, min: usize, max: usize) -> Vec<BoardIndex> {
+    board
+        .unsolved_indexes()
+        .into_iter()
+        .filter(|i| (min..=max).contains(&board.candidates(i).len()))
+        .collect()
+}
+
+// Removes the value from every cell that sees all of the given cells
+fn eliminate_seen_by_all(board: &Board, cells: &[BoardIndex], value: u8) -> Vec<(BoardIndex, u8)> {
+    peers(&cells[0])
+        .iter()
+        .filter(|i| !cells.contains(i))
+        .filter(|i| cells.iter().all(|c| sees(c, i)))
+        .filter(|i| board.has_candidate(i, value))
+        .map(|i| (i.clone(), value))
+        .collect()
+}
+
+fn union(board: &Board, cells: &[BoardIndex]) -> Vec<u8> {
+    let mut digits: Vec<u8> = cells
+        .iter()
+        .flat_map(|i| board.candidates(i).to_vec())
+        .collect();
+    digits.sort();
+    digits.dedup();
+    digits
+}
+
+// A pivot with candidates {x, y} that sees two pincers with {x, z} and
+// {y, z}. Either pincer must be z, so z can be removed from cells that see
+// both pincers.
+pub fn find_xy_wing(board: &Board) -> Option<Step> {
+    let bivalue = cells_with_candidates(board, 2, 2);
+    for pivot in &bivalue {
+        let pivot_digits = board.candidates(pivot);
+        let pincers: Vec<&BoardIndex> = bivalue
+            .iter()
+            .filter(|i| sees(pivot, i))
+            .filter(|i| board.candidates(i).iter().filter(|d| pivot_digits.contains(d)).count() == 1)
+            .collect();
+        for pair in combinations(&pincers, 2) {
+            let (a, b) = (pair[0], pair[1]);
+            let digits = union(board, &[pivot.clone(), a.clone(), b.clone()]);
+            if digits.len() != 3 {
+                continue;
+            }
+            let a_digits = board.candidates(a);
+            let b_digits = board.candidates(b);
+            // The pincers share z and nothing else
+            let z = match a_digits.iter().find(|d| b_digits.contains(d) && !pivot_digits.contains(d)) {
+                Some(z) => *z,
+                None => continue,
+            };
+            if a_digits == b_digits {
+                continue;
+            }
+            let eliminations = eliminate_seen_by_all(board, &[a.clone(), b.clone()], z);
+            if eliminations.is_empty() {
+                continue;
+            }
+            return Some(Step::new(
+                "XY-Wing",
+                vec![pivot.clone(), a.clone(), b.clone()],
+                digits,
+                vec![],
+                board.elimination_diff(&eliminations),
+            ));
+        }
+    }
+    None
+}
+
+// A pivot with candidates {x, y, z} that sees two pincers with {x, z} and
+// {y, z}. One of the three must be z, so z can be removed from cells that
+// see all of them.
+pub fn find_xyz_wing(board: &Board) -> Option<Step> {
+    let bivalue = cells_with_candidates(board, 2, 2);
+    for pivot in cells_with_candidates(board, 3, 3) {
+        let pivot_digits = board.candidates(&pivot);
+        let pincers: Vec<&BoardIndex> = bivalue
+            .iter()
+            .filter(|i| sees(&pivot, i))
+            .filter(|i| board.candidates(i).iter().all(|d| pivot_digits.contains(d)))
+            .collect();
+        for pair in combinations(&pincers, 2) {
+            let (a, b) = (pair[0], pair[1]);
+            let a_digits = board.candidates(a);
+            let b_digits = board.candidates(b);
+            if a_digits == b_digits {
+                continue;
+            }
+            let z = match a_digits.iter().find(|d| b_digits.contains(d)) {
+                Some(z) => *z,
+                None => continue,
+            };
+            let cells = vec![pivot.clone(), a.clone(), b.clone()];
+            let eliminations = eliminate_seen_by_all(board, &cells, z);
+            if eliminations.is_empty() {
+                continue;
+            }
+            return Some(Step::new(
+                "XYZ-Wing",
+                cells,
+                pivot_digits.to_vec(),
+                vec![],
+                board.elimination_diff(&eliminations),
+            ));
+        }
+    }
+    None
+}
+
+// Two cells with the same candidates {x, y} that don't see each other,
+// joined by a house where x only fits in two cells, one seeing each of them.
+// One of the pair must be y, so y can be removed from cells that see both.
+pub fn find_w_wing(board: &Board) -> Option<Step> {
+    let bivalue = cells_with_candidates(board, 2, 2);
+    for pair in combinations(&bivalue, 2) {
+        let (a, b) = (&pair[0], &pair[1]);
+        let digits = board.candidates(a);
+        if digits != board.candidates(b) || sees(a, b) {
+            continue;
+        }
+        for (x, y) in [(digits[0], digits[1]), (digits[1], digits[0])] {
+            let eliminations = eliminate_seen_by_all(board, &[a.clone(), b.clone()], y);
+            if eliminations.is_empty() {
+                continue;
+            }
+            for house in House::all() {
+                let link = positions(board, &house, x);
+                if link.len() != 2 || link.contains(a) || link.contains(b) {
+                    continue;
+                }
+                let joined = (sees(&link[0], a) && sees(&link[1], b))
+                    || (sees(&link[0], b) && sees(&link[1], a));
+                if !joined {
+                    continue;
+                }
+                return Some(Step::new(
+                    "W-Wing",
+                    vec![a.clone(), b.clone(), link[0].clone(), link[1].clone()],
+                    vec![x, y],
+                    vec![house],
+                    board.elimination_diff(&eliminations),
+                ));
+            }
+        }
+    }
+    None
+}
+
+// Four cells holding exactly four digits between them, where every digit but
+// z only appears in cells that all see each other. Those digits can each fill
+// at most one of the cells, so at least one cell must be z, and z can be
+// removed from cells that see every z in the wing.
+pub fn find_wxyz_wing(board: &Board) -> Option<Step> {
+    let candidates = cells_with_candidates(board, 2, 4);
+    for pivot in &candidates {
+        let wings: Vec<&BoardIndex> = candidates
+            .iter()
+            .filter(|i| sees(pivot, i))
+            .filter(|i| union(board, &[pivot.clone(), (*i).clone()]).len() <= 4)
+            .collect();
+        for others in combinations(&wings, 3) {
+            let mut cells: Vec<BoardIndex> = others.into_iter().cloned().collect();
+            cells.insert(0, pivot.clone());
+            let digits = union(board, &cells);
+            if digits.len() != 4 {
+                continue;
+            }
+
+            let unrestricted: Vec<u8> = digits
+                .iter()
+                .cloned()
+                .filter(|d| {
+                    let holders: Vec<&BoardIndex> = cells.iter().filter(|i| board.has_candidate(i, *d)).collect();
+                    combinations(&holders, 2).iter().any(|p| !sees(p[0], p[1]))
+                })
+                .collect();
+            if unrestricted.len() != 1 {
+                continue;
+            }
+
+            let z = unrestricted[0];
+            let holders: Vec<BoardIndex> = cells.iter().filter(|i| board.has_candidate(i, z)).cloned().collect();
+            let eliminations = eliminate_seen_by_all(board, &holders, z);
+            if eliminations.is_empty() {
+                continue;
+            }
+            let diff = board.elimination_diff(&eliminations);
+            return Some(Step::new("WXYZ-Wing", cells, digits, vec![], diff));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::techniques::tests::board_with_options;
+
+    fn eliminated(step: &Step) -> Vec<u8> {
+        step.diff.fragments().iter().map(|f| f.index().value).collect()
+    }
+
+    #[test]
+    fn finds_xy_wing() {
+        // Pivot r1c1 {1,2}, pincers r1c5 {1,3} and r5c1 {2,3}
+        let board = board_with_options(&[(0, &[1, 2]), (4, &[1, 3]), (36, &[2, 3])]);
+        let step = find_xy_wing(&board).unwrap();
+        assert_eq!(step.technique, "XY-Wing");
+        assert_eq!(step.digits, vec![1, 2, 3]);
+        // Only r5c5 sees both pincers
+        assert_eq!(eliminated(&step), vec![40]);
+    }
+
+    #[test]
+    fn finds_xyz_wing() {
+        // Pivot r1c1 {1,2,3}, pincers r1c2 {1,3} and r4c1 {2,3}
+        let board = board_with_options(&[(0, &[1, 2, 3]), (1, &[1, 3]), (27, &[2, 3])]);
+        let step = find_xyz_wing(&board).unwrap();
+        assert_eq!(step.technique, "XYZ-Wing");
+        // Cells seeing all three are in column 0 of box 0
+        assert_eq!(eliminated(&step), vec![9, 18]);
+    }
+
+    #[test]
+    fn finds_w_wing() {
+        // r1c1 and r5c5 are {4,6}, and 4 in row 9 only fits in c1 and c5
+        let mut options: Vec<(u8, &[u8])> = vec![(0, &[4, 6]), (40, &[4, 6])];
+        for i in [73, 74, 75, 77, 78, 79, 80] {
+            options.push((i, &[1, 2, 3, 5, 6, 7, 8, 9]));
+        }
+        let board = board_with_options(&options);
+        let step = find_w_wing(&board).unwrap();
+        assert_eq!(step.technique, "W-Wing");
+        assert_eq!(step.digits, vec![4, 6]);
+        assert_eq!(step.houses, vec![House::Row(8)]);
+        // r1c5 and r5c1 see both ends
+        assert_eq!(eliminated(&step), vec![4, 36]);
+    }
+
+    #[test]
+    fn finds_wxyz_wing() {
+        // Pivot r1c1 {1,2,3} with wings r1c2 {1,4}, r1c6 {2,4} and r2c3
+        // {3,4}, where only 4 is unrestricted
+        let board = board_with_options(&[
+            (0, &[1, 2, 3]),
+            (1, &[1, 4]),
+            (5, &[2, 4]),
+            (11, &[3, 4]),
+        ]);
+        let step = find_wxyz_wing(&board).unwrap();
+        assert_eq!(step.technique, "WXYZ-Wing");
+        assert_eq!(step.digits, vec![1, 2, 3, 4]);
+        // Only r1c3 sees every 4 in the wing
+        assert_eq!(eliminated(&step), vec![2]);
+    }
+}